@@ -0,0 +1,150 @@
+# arabic messages of the ui, `{ $name }` is replaced by the argument `name`
+
+other-language = English
+page-not-found = الصفحة غير موجودة
+
+# xlsx form
+card-title = عنوان الكارت
+card-title-hint = {copy} من {copies} يرقم نسخ الصف
+path = موقع ملف الاكسل
+sheet-name = اسم الشييت
+no-sheet = لا يكن
+auto = تلقائي
+encoding = ترميز الملف
+delimiter = الفاصل
+area = النطاق
+area-used = كل الشييت
+area-table = جدول : { $name }
+area-named = نطاق : { $name }
+skip-hidden = تجاهل المخفي و المفلتر
+title-row = مسلسل صف العناوين
+title-rows-count = عدد صفوف العناوين
+merged-cells = الخلايا المدمجة
+merged-keep = كما هي
+merged-fill = تكرار القيمة
+merged-prefix = عنوان فوق العناوين
+columns = الاعمدة
+card-back = وجه خلفي
+back-text = نص الظهر
+back-text-hint = {اسم العمود} يستبدل بقيمته
+back-columns = أعمدة الظهر
+copies = عدد النسخ
+copies-fixed = ثابت لكل صف
+copies-column = من عمود { $name }
+copies-hint = {copy} من {copies} في العنوان يرقم النسخ
+page-columns = بطاقات في الصف
+dir-auto = تلقائي
+dir-rtl = من اليمين
+dir-ltr = من اليسار
+cards-direction = اتجاه البطاقات
+page-rows = صفوف في الصفحة
+page-rows-hint = يلزم عدد الصفوف لطباعة الظهر خلف الوجه
+digits = الأرقام
+digits-western = غربية 123
+digits-arabic = هندية ١٢٣
+thousands = فاصل الآلاف
+date-format = التواريخ
+date-as-stored = كما في الملف
+date-numeric = سنة/شهر/يوم
+date-arabic = أسماء الشهور بالعربية
+date-english = أسماء الشهور بالإنجليزية
+calendar = التقويم
+calendar-gregorian = ميلادي
+calendar-hijri = هجري
+submit = تمام
+submit-missing = افندم!
+
+# cards
+edit = تعديل
+all = الكل
+skip-first = تخطي أول
+positions = خانات
+source-changed = تغير الملف المصدر و تم تحديث البطاقات
+dismiss = إخفاء
+printed = مطبوع
+changed-since-print = تغير بعد الطباعة
+
+# print run selection
+id-column = المعرف
+row-number = رقم الصف
+from = من
+to = إلى
+select-rows = تحديد الصفوف
+search = بحث
+select-found = تحديد النتائج
+clear-selection = إلغاء التحديد
+selected-count = المحدد { $count }
+print-selected-only = طباعة المحدد فقط
+all-cards = كل البطاقات
+hide-printed = إخفاء المطبوع
+only-changed = المتغير بعد الطباعة فقط
+print = طباعة
+print-confirm = هل طبعت { $count } بطاقة؟ لا تعلم مطبوعة إلا بعد التأكيد
+print-done = تعليم كمطبوعة
+print-failed = لم تطبع
+
+# login
+user-name = اسم المستخدم
+password = كلمة المرور
+login = دخول
+login-failed = اسم المستخدم أو كلمة المرور غير صحيحة
+logout = خروج
+
+# audit
+audit = سجل الوصول
+audit-who = المستخدم أو العنوان
+audit-file = الملف أو الورقة
+audit-all-calls = كل العمليات
+audit-call = العملية
+audit-time = الوقت (UTC)
+audit-rows = الصفوف
+audit-forbidden = سجل الوصول للمشرفين فقط
+
+# validation
+rules = قواعد التحقق
+rule-column = العمود
+rule-required = مطلوب
+rule-regex = نمط
+rule-length = الطول
+rule-range = نطاق رقمي
+rule-unique = غير مكرر
+rule-allowed = قيم مسموحة
+rule-regex-hint = تعبير نمطي
+rule-allowed-hint = قيم تفصلها فواصل
+rule-min = الأدنى
+rule-max = الأقصى
+rule-add = إضافة قاعدة
+exclude-invalid = استبعاد الصفوف المخالفة لقاعدة
+violations-count = { $count } مشكلة في { $rows } صف
+violations-excluded = هذه الصفوف مستبعدة من البطاقات
+violation-column = العمود
+violation-value = القيمة
+violation-rule = القاعدة
+show-cards-anyway = عرض البطاقات رغم ذلك
+duplicate-keys = أعمدة مفتاح التكرار
+duplicates = الصفوف المكررة
+matching-exact = تطابق تام
+matching-normalized = تطابق بعد التوحيد
+keep-all = طباعة الكل
+keep-first = إبقاء الأول
+keep-last = إبقاء الأخير
+duplicate = مكرر
+duplicates-count = { $count } صفوف مكررة
+transforms = التنظيف
+strip-tashkeel = حذف التشكيل
+remove-tatweel = حذف التطويل
+unify-letters = توحيد الألف والياء
+collapse-spaces = دمج المسافات
+trim = حذف المسافات الطرفية
+title-case = أحرف كبيرة أول الكلمات اللاتينية
+ungrouped = بلا فواصل الآلاف، للأرقام القومية وأرقام الجلوس
+max-length = أقصى عدد من الأحرف
+maps = تسميات القيم
+map-inline = مكتوبة هنا
+map-sheet = من ورقة أو ملف csv
+map-add = إضافة التسميات
+map-inline-hint = قيمة = تسمية في كل سطر
+map-path-hint = مسار الورقة التي في أول عمودين منها القيم وتسمياتها تحت صف العناوين
+map-values = { $count } تسميات
+rule-mapped = بلا تسمية
+rule-copies = أكثر من الحد الأقصى للنسخ
@@ -0,0 +1,150 @@
+# english messages of the ui, `{ $name }` is replaced by the argument `name`
+
+other-language = العربية
+page-not-found = Page not found.
+
+# xlsx form
+card-title = Card title
+card-title-hint = {copy} of {copies} numbers the copies of a row
+path = Spreadsheet path
+sheet-name = Sheet name
+no-sheet = None
+auto = Auto
+encoding = File encoding
+delimiter = Delimiter
+area = Area
+area-used = Whole sheet
+area-table = Table: { $name }
+area-named = Range: { $name }
+skip-hidden = Skip hidden and filtered rows
+title-row = Title row number
+title-rows-count = Title rows
+merged-cells = Merged cells
+merged-keep = Keep as is
+merged-fill = Repeat the value
+merged-prefix = Group above the headers
+columns = Columns
+card-back = Card back
+back-text = Back text
+back-text-hint = {column name} is replaced by its value
+back-columns = Back columns
+copies = Copies
+copies-fixed = Fixed for every row
+copies-column = From column { $name }
+copies-hint = {copy} of {copies} in the title numbers the copies
+page-columns = Cards per row
+dir-auto = Auto
+dir-rtl = Right to left
+dir-ltr = Left to right
+cards-direction = Cards direction
+page-rows = Rows per page
+page-rows-hint = Needed to print the backs behind their fronts
+digits = Digits
+digits-western = Western 123
+digits-arabic = Arabic-Indic ١٢٣
+thousands = Thousands separator
+date-format = Dates
+date-as-stored = As stored
+date-numeric = Year/month/day
+date-arabic = Arabic month names
+date-english = English month names
+calendar = Calendar
+calendar-gregorian = Gregorian
+calendar-hijri = Hijri
+submit = Done
+submit-missing = Missing fields!
+
+# cards
+edit = Edit
+all = All
+skip-first = Skip the first
+positions = positions
+source-changed = The source file changed and the cards were refreshed
+dismiss = Dismiss
+printed = Printed
+changed-since-print = Changed since printed
+
+# print run selection
+id-column = Identifier
+row-number = Row number
+from = From
+to = To
+select-rows = Select rows
+search = Search
+select-found = Select results
+clear-selection = Clear selection
+selected-count = Selected { $count }
+print-selected-only = Print selected only
+all-cards = All cards
+hide-printed = Hide printed
+only-changed = Only changed since printed
+print = Print
+print-confirm = Did { $count } cards print? They are only marked printed once confirmed
+print-done = Mark printed
+print-failed = Not printed
+
+# login
+user-name = User name
+password = Password
+login = Log in
+login-failed = Wrong user name or password
+logout = Log out
+
+# audit
+audit = Audit
+audit-who = User or IP
+audit-file = File or sheet
+audit-all-calls = All calls
+audit-call = Call
+audit-time = Time (UTC)
+audit-rows = Rows
+audit-forbidden = Only admins can read the audit trail
+
+# validation
+rules = Validation rules
+rule-column = Column
+rule-required = Required
+rule-regex = Pattern
+rule-length = Length
+rule-range = Number range
+rule-unique = Unique
+rule-allowed = Allowed values
+rule-regex-hint = Regular expression
+rule-allowed-hint = Values separated by commas
+rule-min = Minimum
+rule-max = Maximum
+rule-add = Add rule
+exclude-invalid = Leave out the rows breaking a rule
+violations-count = { $count } problems in { $rows } rows
+violations-excluded = These rows are left out of the cards
+violation-column = Column
+violation-value = Value
+violation-rule = Rule
+show-cards-anyway = Show the cards anyway
+duplicate-keys = Key columns for duplicates
+duplicates = Duplicates
+matching-exact = Exact match
+matching-normalized = Normalized match
+keep-all = Print all
+keep-first = Keep the first
+keep-last = Keep the last
+duplicate = Duplicate
+duplicates-count = { $count } duplicate rows
+transforms = Clean up
+strip-tashkeel = Remove diacritics
+remove-tatweel = Remove tatweel
+unify-letters = Unify alef and yaa
+collapse-spaces = Collapse spaces
+trim = Trim
+title-case = Title Case latin words
+ungrouped = No thousands separator, for ids
+max-length = Most characters
+maps = Value labels
+map-inline = Written here
+map-sheet = From a sheet or csv
+map-add = Add labels
+map-inline-hint = One value = label on every line
+map-path-hint = Path of the sheet with values and labels in its first two columns under a title row
+map-values = { $count } labels
+rule-mapped = Has no label
+rule-copies = More than the most copies
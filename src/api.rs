@@ -0,0 +1,342 @@
+//! json api over the card generation for tools that do not need the ui,
+//! nested under `/api/v1` by the server
+
+use crate::app::{
+    audit,
+    cards::{
+        Calendar, Card, CardBack, CardSide, CardsServerProps, ColumnDirection, ColumnMap,
+        ColumnRule, ColumnTransforms, Copies, DateLocale, Digits, Direction, Duplicates,
+        KeepDuplicates, KeyMatching, Kv, MapEntry, MergedCells, PageLayout, PrintPages, PrintedRow,
+        Rule, SheetArea, SheetSource, TextOptions, Transforms, ValueFormat, ValueMap, Violation,
+    },
+    history::{self, EventKind},
+    i18n::Lang,
+    sheet::{self, Sheet},
+};
+use crate::auth::User;
+use axum::{
+    Extension, Json, Router,
+    extract::{ConnectInfo, Query},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+};
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, num::NonZeroUsize, path::PathBuf};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "kvg", description = "cards generated from spreadsheet rows"),
+    servers((url = "/api/v1")),
+    paths(list_sheets, list_headers, list_cards, validate_cards),
+    components(schemas(
+        SheetsRequest,
+        HeadersRequest,
+        Column,
+        CardsFormat,
+        CardsServerProps,
+        CardBack,
+        PageLayout,
+        Copies,
+        ColumnDirection,
+        Direction,
+        ColumnTransforms,
+        Transforms,
+        ValueFormat,
+        ColumnRule,
+        Rule,
+        Violation,
+        Duplicates,
+        ColumnMap,
+        ValueMap,
+        MapEntry,
+        KeyMatching,
+        KeepDuplicates,
+        Digits,
+        DateLocale,
+        Calendar,
+        SheetSource,
+        SheetArea,
+        MergedCells,
+        TextOptions,
+        Card,
+        CardSide,
+        Kv,
+    ))
+)]
+struct ApiDoc;
+
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/sheets", post(list_sheets))
+        .route("/headers", post(list_headers))
+        .route("/cards", post(list_cards))
+        .route("/validate", post(validate_cards))
+        .route("/openapi.json", get(openapi))
+}
+
+struct ApiError(ServerFnError);
+
+impl<E: Into<ServerFnError>> From<E> for ApiError {
+    fn from(value: E) -> Self {
+        Self(value.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.0.to_string()).into_response()
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct SheetsRequest {
+    #[schema(value_type = String)]
+    path: PathBuf,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct HeadersRequest {
+    source: SheetSource,
+    #[schema(value_type = Option<usize>)]
+    title_row_index: Option<NonZeroUsize>,
+    #[serde(default = "one_row")]
+    #[schema(value_type = usize)]
+    title_rows_count: NonZeroUsize,
+}
+
+fn one_row() -> NonZeroUsize {
+    NonZeroUsize::MIN
+}
+
+#[derive(Serialize, ToSchema)]
+struct Column {
+    /// the value to put in `columns_indexs`
+    index: usize,
+    name: String,
+}
+
+/// the formats of the cards, there is no pdf: the html page is printed or
+/// saved as a pdf by the browser, which lays out the pages and their fonts
+#[derive(Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum CardsFormat {
+    #[default]
+    Json,
+    /// a printable page of the cards
+    Html,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CardsQuery {
+    #[serde(default)]
+    format: CardsFormat,
+    /// the title written on every card of the html page
+    #[serde(default)]
+    title: String,
+    /// how many cards to skip
+    #[serde(default)]
+    offset: usize,
+    /// the most cards to return, all of them when missing
+    limit: Option<usize>,
+    /// the language of the html page, `ar` or `en`, the one of the server
+    /// when missing
+    lang: Option<String>,
+}
+
+/// names of the sheets of a workbook
+#[utoipa::path(
+    post,
+    path = "/sheets",
+    request_body = SheetsRequest,
+    responses(
+        (status = 200, body = Vec<String>),
+        (status = 400, description = "the file can not be read", body = String),
+    )
+)]
+async fn list_sheets(
+    Extension(user): Extension<User>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<SheetsRequest>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let names = user
+        .check_path(&req.path)
+        .and_then(|_| sheet::sheets_names(&req.path));
+    audit::record_by(
+        Some(&user),
+        Some(addr.ip()),
+        "api_sheets",
+        &req.path,
+        None,
+        &names,
+        |x| Some(x.len()),
+    )?;
+    Ok(Json(names?))
+}
+
+/// the visible columns of a sheet named by its title rows
+#[utoipa::path(
+    post,
+    path = "/headers",
+    request_body = HeadersRequest,
+    responses(
+        (status = 200, body = Vec<Column>),
+        (status = 400, description = "the sheet can not be read", body = String),
+    )
+)]
+async fn list_headers(
+    Extension(user): Extension<User>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<HeadersRequest>,
+) -> Result<Json<Vec<Column>>, ApiError> {
+    let columns = user.check_path(&req.source.path).and_then(|_| {
+        let sheet = Sheet::open(&req.source)?;
+        Ok(sheet
+            .headers(req.title_row_index, req.title_rows_count)?
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| sheet.is_column_visible(*index))
+            .map(|(index, name)| Column { index, name })
+            .collect::<Vec<_>>())
+    });
+    audit::record_by(
+        Some(&user),
+        Some(addr.ip()),
+        "api_headers",
+        &req.source.path,
+        Some(&req.source.sheet),
+        &columns,
+        |x| Some(x.len()),
+    )?;
+    Ok(Json(columns?))
+}
+
+/// the cards of the visible rows as json or as a printable html page, a pdf
+/// is made by printing the page from a browser
+#[utoipa::path(
+    post,
+    path = "/cards",
+    params(CardsQuery),
+    request_body = CardsServerProps,
+    responses(
+        (
+            status = 200,
+            body = Vec<Card>,
+            content_type = "application/json",
+            headers(("x-total-count" = usize, description = "how many cards the whole sheet makes")),
+        ),
+        (status = 200, body = String, content_type = "text/html"),
+        (status = 400, description = "the sheet can not be read", body = String),
+    )
+)]
+async fn list_cards(
+    Extension(user): Extension<User>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<CardsQuery>,
+    Json(req): Json<CardsServerProps>,
+) -> Result<Response, ApiError> {
+    let (path, sheet) = (req.source.path.clone(), req.source.sheet.clone());
+    let layout = req.layout;
+    let checked = req.paths().try_for_each(|x| user.check_path(x));
+    let page = checked.and_then(|_| sheet::build_cards(req, query.offset, query.limit));
+    audit::record_by(
+        Some(&user),
+        Some(addr.ip()),
+        "api_cards",
+        &path,
+        Some(&sheet),
+        &page,
+        |x| Some(x.cards.len()),
+    )?;
+    let page = page?;
+    let total = [("x-total-count", page.total.to_string())];
+    let lang = match query.lang.as_deref() {
+        Some(x) => Lang::from_code(x)
+            .ok_or_else(|| ServerFnError::new(format!("language {x} is not ar or en")))?,
+        None => crate::config::config().language,
+    };
+    let response = match query.format {
+        CardsFormat::Json => (total, Json(page.cards)).into_response(),
+        CardsFormat::Html => {
+            // the printable page is recorded like a print from the ui with the
+            // title as the project and the row numbers as ids
+            let rows = page
+                .cards
+                .iter()
+                .map(|card| PrintedRow {
+                    id: (card.row_index + 1).to_string(),
+                    digest: card.digest(),
+                })
+                .collect();
+            history::record(EventKind::Export, &user, query.title.clone(), path, rows)?;
+            (
+                total,
+                Html(cards_page(query.title, page.cards, layout, lang)),
+            )
+                .into_response()
+        }
+    };
+    Ok(response)
+}
+
+/// every cell of the card rows breaking one of the rules or missing from the
+/// map of its column
+#[utoipa::path(
+    post,
+    path = "/validate",
+    request_body = CardsServerProps,
+    responses(
+        (status = 200, body = Vec<Violation>),
+        (status = 400, description = "the sheet can not be read or a pattern is not valid", body = String),
+    )
+)]
+async fn validate_cards(
+    Extension(user): Extension<User>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<CardsServerProps>,
+) -> Result<Json<Vec<Violation>>, ApiError> {
+    let violations = req
+        .paths()
+        .try_for_each(|x| user.check_path(x))
+        .and_then(|_| sheet::validate_rows(&req));
+    audit::record_by(
+        Some(&user),
+        Some(addr.ip()),
+        "api_validate",
+        &req.source.path,
+        Some(&req.source.sheet),
+        &violations,
+        |x| Some(x.len()),
+    )?;
+    Ok(Json(violations?))
+}
+
+/// the printable page of `cards`, laid out in the direction of the card set
+/// or else of `lang`
+fn cards_page(title: String, cards: Vec<Card>, layout: PageLayout, lang: Lang) -> String {
+    let dir = layout.dir.map_or(lang.dir(), Direction::attr);
+    let owner = Owner::new();
+    owner.with(|| {
+        let page = view! {
+            <!DOCTYPE html>
+            <html dir=dir lang=lang.code()>
+                <head>
+                    <meta charset="utf-8"/>
+                    <title>{title.clone()}</title>
+                    <link rel="stylesheet" href="/pkg/kvg.css"/>
+                </head>
+                <body>
+                    <PrintPages title=title.clone() cards=Signal::stored(cards) layout/>
+                </body>
+            </html>
+        };
+        page.to_html()
+    })
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
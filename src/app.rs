@@ -1,20 +1,30 @@
+use audit::{AuditLink, AuditPage};
 use cards::{Cards, CardsServerProps};
+use i18n::{LanguageSwitch, provide_locale, tr};
 use leptos::prelude::*;
-use leptos_meta::{MetaTags, Stylesheet, Title, provide_meta_context};
+use leptos_meta::{Html, MetaTags, Stylesheet, Title, provide_meta_context};
 use leptos_router::{
     StaticSegment,
     components::{Route, Router, Routes},
 };
+use login::{LoginPage, LogoutButton};
 
 use crate::app::xlsx_form::XlsxForm;
 
-mod cards;
+pub(crate) mod audit;
+pub(crate) mod cards;
+#[cfg(feature = "ssr")]
+pub(crate) mod history;
+pub(crate) mod i18n;
+mod login;
+#[cfg(feature = "ssr")]
+pub(crate) mod sheet;
 mod xlsx_form;
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
         <!DOCTYPE html>
-        <html dir="rtl" lang="en">
+        <html>
             <head>
                 <meta charset="utf-8"/>
                 <meta name="viewport" content="width=device-width, initial-scale=1"/>
@@ -33,8 +43,11 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    let lang = provide_locale();
 
     view! {
+        <Html {..} lang=move || lang.get().code() dir=move || lang.get().dir()/>
+
         // injects a stylesheet into the document <head>
         // id=leptos means cargo-leptos will hot-reload this stylesheet
         <Stylesheet id="leptos" href="/pkg/kvg.css"/>
@@ -45,9 +58,16 @@ pub fn App() -> impl IntoView {
         // content for this welcome page
         <Router>
             <main class="m-4">
-                <p class="text-xs text-left p-3 print:hidden">made by mahmoud eltahawy</p>
-                <Routes fallback=|| "Page not found.".into_view()>
+                <div class="flex justify-between print:hidden">
+                    <LanguageSwitch/>
+                    <p class="text-xs p-3">made by mahmoud eltahawy</p>
+                    <AuditLink/>
+                    <LogoutButton/>
+                </div>
+                <Routes fallback=move || tr("page-not-found")()>
                     <Route path=StaticSegment("") view=HomePage/>
+                    <Route path=StaticSegment("login") view=LoginPage/>
+                    <Route path=StaticSegment("audit") view=AuditPage/>
                 </Routes>
             </main>
         </Router>
@@ -58,16 +78,19 @@ pub fn App() -> impl IntoView {
 fn HomePage() -> impl IntoView {
     let csp = RwSignal::new(None::<CardsServerProps>);
     let title = RwSignal::new(String::new());
+    let editing = RwSignal::new(true);
 
     let xlsx_form = move || {
         view! {
-            <XlsxForm title csp/>
+            <XlsxForm title csp editing/>
         }
     };
 
+    let cards = move || csp.get().filter(|_| !editing.get());
+
     view! {
-        <ShowLet some=csp let:csp fallback=xlsx_form>
-            <Cards title=title.get() csp/>
+        <ShowLet some=cards let:csp fallback=xlsx_form>
+            <Cards title=title.get() csp editing/>
         </ShowLet>
     }
 }
@@ -0,0 +1,268 @@
+//! every opening of a file through the server functions appended as a json
+//! line with who asked for it, searched by admins on the audit page
+
+use super::i18n::tr;
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[cfg(feature = "ssr")]
+/// the audit trail in the data directory
+const AUDIT_FILE: &str = "audit.jsonl";
+/// the most records a search shows, the newest ones
+#[cfg(feature = "ssr")]
+const MAX_RESULTS: usize = 500;
+
+/// the server functions and api endpoints whose calls are recorded
+const CALLS: [&str; 14] = [
+    "get_cards",
+    "get_headers",
+    "sheets_names",
+    "path_exists",
+    "path_autocomplete",
+    "preview_rows",
+    "guess_title_row",
+    "sheet_areas",
+    "validate_cards",
+    "api_sheets",
+    "api_headers",
+    "api_cards",
+    "api_validate",
+    "watch",
+];
+
+#[cfg(feature = "ssr")]
+static WRITING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditRecord {
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+    pub user: Option<String>,
+    pub ip: Option<String>,
+    pub call: String,
+    pub path: PathBuf,
+    pub sheet: Option<String>,
+    /// how many cards, columns, sheets or paths the call returned, `None`
+    /// when it failed or returns a single answer
+    pub rows: Option<usize>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// appends the call of the server function `call` on `path` with its
+/// `result`, failing the call when it can not be recorded
+#[cfg(feature = "ssr")]
+pub fn record<T>(
+    call: &'static str,
+    path: &std::path::Path,
+    sheet: Option<&str>,
+    result: &Result<T, ServerFnError>,
+    rows: impl Fn(&T) -> Option<usize>,
+) -> Result<(), ServerFnError> {
+    use axum::{extract::ConnectInfo, http::request::Parts};
+    use std::net::SocketAddr;
+
+    let parts = use_context::<Parts>();
+    let ip = parts
+        .as_ref()
+        .and_then(|x| x.extensions.get::<ConnectInfo<SocketAddr>>())
+        .map(|x| x.0.ip());
+    let user = crate::auth::current_user().ok();
+    record_by(user.as_ref(), ip, call, path, sheet, result, rows)
+}
+
+/// like [`record`] for a request served outside the server functions, made
+/// by `user` from `ip`
+#[cfg(feature = "ssr")]
+pub fn record_by<T>(
+    user: Option<&crate::auth::User>,
+    ip: Option<std::net::IpAddr>,
+    call: &'static str,
+    path: &std::path::Path,
+    sheet: Option<&str>,
+    result: &Result<T, ServerFnError>,
+    rows: impl Fn(&T) -> Option<usize>,
+) -> Result<(), ServerFnError> {
+    use std::{
+        io::Write,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    let record = AuditRecord {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        user: user.map(|x| x.name.clone()),
+        ip: ip.map(|x| x.to_string()),
+        call: call.to_string(),
+        path: path.to_path_buf(),
+        sheet: sheet.map(str::to_string),
+        rows: result.as_ref().ok().and_then(rows),
+        error: result.as_ref().err().map(|x| x.to_string()),
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let _writing = WRITING.lock().unwrap_or_else(|x| x.into_inner());
+    let path = crate::config::config().data_dir.join(AUDIT_FILE);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// what the records shown on the audit page contain, empty fields match all
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AuditSearch {
+    /// part of the user name or of the ip
+    pub who: String,
+    /// part of the path or of the sheet name
+    pub file: String,
+    pub call: String,
+}
+
+#[server]
+async fn search_audit(search: AuditSearch) -> Result<Vec<AuditRecord>, ServerFnError> {
+    use std::io::{BufRead, BufReader};
+
+    if !crate::auth::current_user()?.admin {
+        return Err(ServerFnError::new("only admins can read the audit trail"));
+    }
+    let file = match std::fs::File::open(crate::config::config().data_dir.join(AUDIT_FILE)) {
+        Ok(x) => x,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let who = search.who.trim().to_lowercase();
+    let file_part = search.file.trim().to_lowercase();
+    let contains = |x: Option<&str>, part: &str| x.is_some_and(|x| x.to_lowercase().contains(part));
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let Ok(record) = serde_json::from_str::<AuditRecord>(&line?) else {
+            continue;
+        };
+        let matches = (who.is_empty()
+            || contains(record.user.as_deref(), &who)
+            || contains(record.ip.as_deref(), &who))
+            && (file_part.is_empty()
+                || contains(record.path.to_str(), &file_part)
+                || contains(record.sheet.as_deref(), &file_part))
+            && (search.call.is_empty() || record.call == search.call);
+        if matches {
+            records.push(record);
+        }
+    }
+    let skip = records.len().saturating_sub(MAX_RESULTS);
+    Ok(records.into_iter().skip(skip).rev().collect())
+}
+
+#[server]
+async fn is_admin() -> Result<bool, ServerFnError> {
+    Ok(crate::auth::current_user()?.admin)
+}
+
+/// `secs` since the unix epoch as a utc date and time
+fn utc(secs: u64) -> String {
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+    // the civil date of a day count, after howard hinnant
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[component]
+pub fn AuditPage() -> impl IntoView {
+    let search = RwSignal::new(AuditSearch::default());
+    let records_res = Resource::new(move || search.get(), search_audit);
+    let input = |label: &'static str, set: fn(&mut AuditSearch, String)| {
+        view! {
+            <input
+                type="search"
+                placeholder=tr(label)
+                class="border-2 rounded-lg p-2"
+                on:change:target=move |ev| {
+                    let value = ev.target().value();
+                    search.update(|x| set(x, value));
+                }
+            />
+        }
+    };
+    view! {
+        <div class="flex flex-wrap gap-4 items-center m-2">
+            {input("audit-who", |x, value| x.who = value)}
+            {input("audit-file", |x, value| x.file = value)}
+            <select
+                class="border-2 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    let value = ev.target().value();
+                    search.update(|x| x.call = value);
+                }
+            >
+                <option value="">{tr("audit-all-calls")}</option>
+                {CALLS.map(|call| view! { <option value=call>{call}</option> })}
+            </select>
+        </div>
+        <Suspense>
+            {move || records_res.get().map(|records| match records {
+                Err(_) => view! { <p class="text-red-600 m-2">{tr("audit-forbidden")}</p> }.into_any(),
+                Ok(records) => view! {
+                    <table class="w-full text-sm border-collapse">
+                        <thead>
+                            <tr class="border-b-2">
+                                <th class="p-2">{tr("audit-time")}</th>
+                                <th class="p-2">{tr("audit-who")}</th>
+                                <th class="p-2">{tr("audit-call")}</th>
+                                <th class="p-2">{tr("audit-file")}</th>
+                                <th class="p-2">{tr("audit-rows")}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {records.into_iter().map(|record| view! {
+                                <tr class="border-b">
+                                    <td class="p-2" dir="ltr">{utc(record.timestamp)}</td>
+                                    <td class="p-2"><bdi>{record.user}</bdi>" "<bdi>{record.ip}</bdi></td>
+                                    <td class="p-2">{record.call}</td>
+                                    <td class="p-2">
+                                        <bdi>{record.path.display().to_string()}</bdi>
+                                        " "
+                                        <bdi>{record.sheet}</bdi>
+                                    </td>
+                                    <td class="p-2">{record.rows}" "{record.error}</td>
+                                </tr>
+                            }).collect_view()}
+                        </tbody>
+                    </table>
+                }.into_any(),
+            })}
+        </Suspense>
+    }
+}
+
+/// a link to the audit page shown to admins only
+#[component]
+pub fn AuditLink() -> impl IntoView {
+    let admin = Resource::new(|| (), |_| is_admin());
+    view! {
+        <Suspense>
+            <Show when=move || matches!(admin.get(), Some(Ok(true)))>
+                <a href="/audit" class="text-xs p-3 print:hidden">{tr("audit")}</a>
+            </Show>
+        </Suspense>
+    }
+}
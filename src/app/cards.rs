@@ -1,110 +1,1192 @@
+use super::i18n::{message, tr, use_lang};
 use leptos::prelude::*;
+use report::ValidationReport;
+use selection::{PrintState, Selection, SelectionBar};
 use serde::{Deserialize, Serialize};
-use std::{num::NonZeroUsize, path::PathBuf};
+use std::{
+    num::NonZeroUsize,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+mod report;
+mod selection;
+
+/// how many cards are fetched at once while the view fills up
+const PAGE_SIZE: usize = 300;
+/// how many cards make one batch to print, a multiple of every count of
+/// columns up to `MAX_PAGE_COLUMNS` so a batch ends on a full row
+const PRINT_BATCH: usize = 600;
+/// the most cards a single row makes, larger counts are cut to it
+pub const MAX_COPIES: usize = 1000;
+/// the most cards a printed row can hold
+pub const MAX_PAGE_COLUMNS: usize = 6;
+/// the most rows of cards a printed page can hold
+pub const MAX_PAGE_ROWS: usize = 30;
 
 #[component]
-pub fn Cards(title: String, csp: CardsServerProps) -> impl IntoView {
-    let cards = Resource::new(move || csp.clone(), get_cards);
-    let cardsfn = move || cards.get().transpose().ok().flatten().unwrap_or_default();
+pub fn Cards(title: String, csp: CardsServerProps, editing: RwSignal<bool>) -> impl IntoView {
+    // a large set is laid out a batch at a time unless all of it is asked for
+    let batch = RwSignal::new(Some(0usize));
+    let cards = RwSignal::new(Vec::<Card>::new());
+    // the part of `cards` every fetch added, rendered once when it comes
+    let fetched = RwSignal::new(Vec::<Range<usize>>::new());
+    let total = RwSignal::new(None::<usize>);
+    let duplicates = RwSignal::new(0usize);
+    let keep = RwSignal::new(csp.duplicates.keep);
+    let loading = RwSignal::new(true);
+    // why the last fetch of cards failed
+    let error = RwSignal::new(None::<String>);
+    // bumped on every reload so a stale fetch loop stops appending
+    let generation = StoredValue::new(0usize);
+    // bumped when the source file is saved again
+    let revision = RwSignal::new(0usize);
+    let changed = RwSignal::new(false);
+    // the cards wait for the validation report of a set with checks
+    let reviewed = RwSignal::new(!csp.has_checks());
+    let report_csp = csp.clone();
+    let selection = Selection::new();
+    let path = csp.source.path.clone();
+    let layout = RwSignal::new(csp.layout);
+    let print_title = title.clone();
+    // the title and the file name the project the print history is kept for
+    let (project, history_path) = (title.clone(), path.clone());
+    Effect::new(move || selection.load_history(project.clone(), history_path.clone()));
+
+    #[cfg(feature = "hydrate")]
+    {
+        let path = csp.source.path.clone();
+        Effect::new(move || {
+            watch_source(&path, move || {
+                changed.set(true);
+                revision.update(|x| *x += 1);
+            })
+        });
+    }
+
+    Effect::new(move || {
+        revision.track();
+        let (start, end) = match batch.get() {
+            Some(x) => (x * PRINT_BATCH, Some((x + 1) * PRINT_BATCH)),
+            None => (0, None),
+        };
+        let current = generation.get_value() + 1;
+        generation.set_value(current);
+        cards.set(Vec::new());
+        fetched.set(Vec::new());
+        error.set(None);
+        loading.set(true);
+        let mut csp = csp.clone();
+        csp.duplicates.keep = keep.get();
+        leptos::task::spawn_local(async move {
+            let mut offset = start;
+            loop {
+                let limit = end.map_or(PAGE_SIZE, |end| PAGE_SIZE.min(end - offset));
+                let page = get_cards(csp.clone(), offset, limit).await;
+                if generation.get_value() != current {
+                    return;
+                }
+                let page = match page {
+                    Ok(x) => x,
+                    Err(e) => {
+                        error.set(Some(e.to_string()));
+                        break;
+                    }
+                };
+                let done = page.cards.is_empty();
+                offset += page.cards.len();
+                total.set(Some(page.total));
+                duplicates.set(page.duplicates);
+                let added = cards.with_untracked(|x| x.len()..x.len() + page.cards.len());
+                cards.update(|x| x.extend(page.cards));
+                fetched.update(|x| x.push(added));
+                if done || offset >= end.unwrap_or(page.total).min(page.total) {
+                    break;
+                }
+            }
+            loading.set(false);
+        });
+    });
+
+    let batches = move || {
+        total
+            .get()
+            .map(|x| x.div_ceil(PRINT_BATCH))
+            .unwrap_or_default()
+    };
+    // laid out only once every card is there, as the pages move with each card
+    let printable = Signal::derive(move || {
+        if loading.get() {
+            return Vec::new();
+        }
+        cards.with(|x| {
+            x.iter()
+                .filter(|card| !selection.is_skipped(card))
+                .cloned()
+                .collect()
+        })
+    });
+    let progress = move || {
+        let loaded = cards.read().len();
+        match (loading.get(), total.get()) {
+            (true, Some(total)) => format!("{loaded} / {total}"),
+            (true, None) => String::from("..."),
+            (false, _) => loaded.to_string(),
+        }
+    };
+    let lang = use_lang();
+    let keep_options = [
+        (KeepDuplicates::All, "keep-all"),
+        (KeepDuplicates::First, "keep-first"),
+        (KeepDuplicates::Last, "keep-last"),
+    ];
 
     view! {
-        <Transition>
-        <div class="grid grid-cols-3 gap-1">
-            <For
-                each=cardsfn
-                key=|x| x.row_index
-                let(Card { row_index:_, kv })
+        <ValidationReport csp=report_csp revision editing reviewed/>
+        <div class:hidden=move || !reviewed.get()>
+            <div class="flex gap-4 items-center print:hidden">
+                <button
+                    on:click=move |_| editing.set(true)
+                    class="text-xl font-bold border-2 rounded-xl p-2 m-2 hover:cursor-pointer"
+                >{tr("edit")}</button>
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| {
+                        batch.set(ev.target().value().parse::<usize>().ok());
+                    }
                 >
-                    <div class="break-inside-avoid border-sky-500 border-5 rounded-xl p-1 m-1 text-xl text-center">
-                        <h2 class="font-bold font-xl underline">{title.clone()}</h2>
-                        <dl class="divide-y divide-white/10">
-                            <For
-                                each=move || kv.clone()
-                                key=|x| x.key.clone()
-                                let(Kv { key, value })
-                            >
-                                 <div class="flex">
-                                    <dt class="text-sm px-2 border-l-2 border-dotted font-bold">{key}</dt>
-                                    <dd class="grow text-sm">{value}</dd>
-                                </div>
-                            </For>
-                        </dl>
-                    </div>
-            </For>
+                    <option value="" selected=move || batch.get().is_none()>{tr("all")}</option>
+                    {
+                        move || (0..batches()).map(|i| {
+                            let last = ((i + 1) * PRINT_BATCH).min(total.get().unwrap_or_default());
+                            view! {
+                                <option value={i} selected=move || batch.get() == Some(i)>
+                                    {format!("{} - {last}", i * PRINT_BATCH + 1)}
+                                </option>
+                            }
+                        }).collect_view()
+                    }
+                </select>
+                <span>{progress}</span>
+                <label class="flex gap-2 items-center">
+                    {tr("skip-first")}
+                    <input
+                        type="number"
+                        min="0"
+                        max=move || layout.read().skipped().max(layout.read().skip)
+                        class="border-2 rounded-lg p-2 w-20 text-center"
+                        value=layout.get_untracked().skip
+                        on:input:target=move |ev| {
+                            let skip = ev.target().value().trim().parse::<usize>().unwrap_or_default();
+                            layout.update(|x| x.skip = skip);
+                        }
+                    />
+                    {tr("positions")}
+                </label>
+                <Show when=move || duplicates.get() != 0>
+                    <span class="text-rose-700">{move || message(
+                        lang.get(),
+                        "duplicates-count",
+                        &[("count", &duplicates.get().to_string())],
+                    )}</span>
+                    <select
+                        class="border-2 rounded-lg p-2 text-center"
+                        on:change:target=move |ev| {
+                            if let Some((x, _)) = ev.target().value().parse::<usize>().ok().and_then(|i| keep_options.get(i)) {
+                                keep.set(*x);
+                            }
+                        }
+                    >
+                        {keep_options.into_iter().enumerate().map(|(i, (x, label))| view! {
+                            <option value={i} selected=move || keep.get() == x>{tr(label)}</option>
+                        }).collect_view()}
+                    </select>
+                </Show>
+            </div>
+            {move || error.get().map(|e| view! {
+                <p class="text-red-600 m-2 print:hidden">{e}</p>
+            })}
+            <Show when=move || changed.get()>
+                <div class="flex gap-4 items-center bg-amber-100 border-2 border-amber-500 rounded-lg p-2 m-2 print:hidden">
+                    <span class="grow">{tr("source-changed")}</span>
+                    <button
+                        on:click=move |_| changed.set(false)
+                        class="border-2 rounded-lg px-2 hover:cursor-pointer"
+                    >{tr("dismiss")}</button>
+                </div>
+            </Show>
+            <SelectionBar cards printable loading selection project=title.clone() path/>
+            <div
+                class="grid gap-1 print:hidden"
+                style=move || format!(
+                    "grid-template-columns: repeat({}, minmax(0, 1fr));",
+                    layout.read().columns
+                )
+            >
+                {move || (0..layout.read().skipped()).map(|_| view! {
+                    <div class="border-2 border-dashed rounded-xl m-1 opacity-40"></div>
+                }).collect_view()}
+                <For
+                    each=move || fetched.get()
+                    key=|x| x.start
+                    let(added)
+                >
+                    {cards.with_untracked(|x| x[added].to_vec()).into_iter().map(|card| view! {
+                        <SelectableCard title=title.clone() card selection dir=layout.get_untracked().dir/>
+                    }).collect_view()}
+                </For>
+            </div>
+            <div class="hidden print:block">
+                <PrintPages title=print_title cards=printable layout/>
+            </div>
+        </div>
+    }
+}
+
+/// calls `on_change` every time the server sees the file at `path` saved,
+/// until the calling owner is cleaned up
+#[cfg(feature = "hydrate")]
+fn watch_source(path: &Path, on_change: impl Fn() + 'static) {
+    use wasm_bindgen::{JsCast, closure::Closure};
+
+    let path = js_sys::encode_uri_component(&path.to_string_lossy());
+    let Ok(source) = web_sys::EventSource::new(&format!("/events/watch?path={path}")) else {
+        return;
+    };
+    let listener = Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |_| on_change());
+    let _ = source.add_event_listener_with_callback("changed", listener.as_ref().unchecked_ref());
+    let watching = send_wrapper::SendWrapper::new((source, listener));
+    on_cleanup(move || watching.0.close());
+}
+
+/// a card toggled in and out of the selection by clicking it
+#[component]
+fn SelectableCard(
+    title: String,
+    card: Card,
+    selection: Selection,
+    dir: Option<Direction>,
+) -> impl IntoView {
+    let card = StoredValue::new(card);
+    view! {
+        <div
+            on:click=move |_| card.with_value(|x| selection.toggle(x))
+            class="hover:cursor-pointer"
+            class=("hidden", move || card.with_value(|x| selection.is_hidden(x)))
+            class=("print:hidden", move || card.with_value(|x| selection.is_skipped(x)))
+            class=(["ring-4", "ring-amber-400", "rounded-xl", "print:ring-0"], move || {
+                card.with_value(|x| selection.is_selected(x))
+            })
+            class=(["outline-4", "outline-dashed", "outline-rose-500", "rounded-xl", "print:outline-0"], move || {
+                card.with_value(|x| x.duplicate)
+            })
+        >
+            <Show when=move || card.with_value(|x| x.duplicate)>
+                <span class="text-xs text-rose-700 print:hidden">{tr("duplicate")}</span>
+            </Show>
+            {move || match card.with_value(|x| selection.print_state(x)) {
+                PrintState::New => None,
+                PrintState::Printed => Some(view! {
+                    <span class="text-xs text-green-700 print:hidden">{tr("printed")}</span>
+                }),
+                PrintState::Changed => Some(view! {
+                    <span class="text-xs text-red-700 print:hidden">{tr("changed-since-print")}</span>
+                }),
+            }}
+            <CardView title card=card.get_value() dir/>
+        </div>
+    }
+}
+
+/// a card laid out in `dir`, or in the direction of the page when `None`.
+/// every key and value is isolated so mixed scripts keep their own order
+#[component]
+pub fn CardView(title: String, card: Card, dir: Option<Direction>) -> impl IntoView {
+    let title = fill_template(&title, |x| card.variable(x));
+    view! {
+        <div
+            dir=dir.map(Direction::attr)
+            class="break-inside-avoid border-sky-500 border-5 rounded-xl p-1 m-1 text-xl text-center"
+        >
+            <h2 class="font-bold font-xl underline"><bdi>{title}</bdi></h2>
+            <KvList kv=card.kv/>
+        </div>
+    }
+}
+
+#[component]
+fn CardBackView(side: CardSide, dir: Option<Direction>) -> impl IntoView {
+    view! {
+        <div
+            dir=dir.map(Direction::attr)
+            class="break-inside-avoid border-sky-500 border-5 rounded-xl p-1 m-1 text-xl text-center"
+        >
+            <p dir="auto" class="text-sm whitespace-pre-line">{side.text}</p>
+            <KvList kv=side.kv/>
         </div>
-        </Transition>
+    }
+}
+
+#[component]
+fn KvList(kv: Vec<Kv>) -> impl IntoView {
+    view! {
+        <dl class="divide-y divide-white/10">
+            {
+                kv.into_iter().map(|Kv { key, value, dir }| view! {
+                     <div class="flex">
+                        <dt class="text-sm px-2 border-e-2 border-dotted font-bold"><bdi>{key}</bdi></dt>
+                        <dd class="grow text-sm"><bdi dir=dir.map(Direction::attr)>{value}</bdi></dd>
+                    </div>
+                }).collect_view()
+            }
+        </dl>
+    }
+}
+
+/// a printed page of cards, the slots hold indexes into the printed cards
+pub struct Page {
+    pub back: bool,
+    pub slots: Vec<Option<usize>>,
+}
+
+/// the cards split over pages of `layout`. when the cards have backs every
+/// front page is followed by the page of its backs with each row mirrored,
+/// so that printing on both sides puts every back behind its front
+pub fn print_pages(cards: &[Card], layout: &PageLayout) -> Vec<Page> {
+    let columns = layout.columns.get();
+    let slots = std::iter::repeat_n(None, layout.skipped())
+        .chain((0..cards.len()).map(Some))
+        .collect::<Vec<_>>();
+    let per_page = layout
+        .rows
+        .map_or(slots.len(), |x| x.get().saturating_mul(columns))
+        .max(1);
+    let has_backs = cards.iter().any(|x| x.back.is_some());
+    let mut pages = Vec::new();
+    for page in slots.chunks(per_page) {
+        let mut slots = page.to_vec();
+        if !has_backs {
+            pages.push(Page { back: false, slots });
+            continue;
+        }
+        // a short last row is filled so its backs land on the mirrored side
+        slots.resize(slots.len().div_ceil(columns).saturating_mul(columns), None);
+        let backs = slots
+            .chunks(columns)
+            .flat_map(|row| row.iter().rev().copied())
+            .collect();
+        pages.push(Page { back: false, slots });
+        pages.push(Page {
+            back: true,
+            slots: backs,
+        });
+    }
+    pages
+}
+
+#[component]
+pub fn PrintPages(
+    title: String,
+    cards: Signal<Vec<Card>>,
+    #[prop(into)] layout: Signal<PageLayout>,
+) -> impl IntoView {
+    move || {
+        let layout = layout.get();
+        let style = layout.grid_style();
+        cards.with(|cards| {
+            print_pages(cards, &layout)
+                .into_iter()
+                .map(|page| {
+                    let slots = page
+                        .slots
+                        .into_iter()
+                        .map(|slot| match slot.map(|x| cards[x].clone()) {
+                            Some(Card {
+                                back: Some(side), ..
+                            }) if page.back => {
+                                view! { <CardBackView side dir=layout.dir/> }.into_any()
+                            }
+                            Some(card) if !page.back => {
+                                view! { <CardView title=title.clone() card dir=layout.dir/> }
+                                    .into_any()
+                            }
+                            _ => view! { <div></div> }.into_any(),
+                        })
+                        .collect_view();
+                    let class = if layout.rows.is_some() {
+                        "grid gap-1 break-after-page"
+                    } else {
+                        "grid gap-1"
+                    };
+                    view! { <div class=class style=style.clone()>{slots}</div> }
+                })
+                .collect_view()
+        })
     }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Card {
     pub row_index: usize,
+    /// the number of the row in the spreadsheet starting from 1
+    #[serde(default)]
+    pub sheet_row: usize,
     pub kv: Vec<Kv>,
+    #[serde(default)]
+    pub back: Option<CardSide>,
+    /// the number of this copy of the row starting from 1
+    pub copy: usize,
+    /// how many copies the row makes
+    pub copies: usize,
+    /// another row has the same key
+    #[serde(default)]
+    pub duplicate: bool,
 }
 
+/// the filled back side of a card
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CardSide {
+    pub text: String,
+    pub kv: Vec<Kv>,
+}
+
+impl Card {
+    /// the values of the template variables `{copy}` and `{copies}`
+    pub fn variable(&self, name: &str) -> Option<String> {
+        match name {
+            "copy" => Some(self.copy.to_string()),
+            "copies" => Some(self.copies.to_string()),
+            _ => None,
+        }
+    }
+
+    /// fnv-1a hash of the content of the card, stable between runs so it
+    /// can be compared with the one recorded when the card was printed
+    pub fn digest(&self) -> u64 {
+        let mut hash = 0xcbf29ce484222325u64;
+        let back = self.back.iter().flat_map(|x| x.kv.iter());
+        for Kv { key, value, .. } in self.kv.iter().chain(back) {
+            for byte in key.bytes().chain([0]).chain(value.bytes()).chain([0]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+}
+
+/// `template` with every `{name}` replaced by `value_of(name)`, names without
+/// a value are left as written
+pub fn fill_template(template: &str, value_of: impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find('}')
+            .and_then(|end| Some((end, value_of(after[..end].trim())?)))
+        {
+            Some((end, value)) => {
+                filled.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// a card as it was when printed, identified by its row number or the value
+/// of the chosen id column
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PrintedRow {
+    pub id: String,
+    pub digest: u64,
+}
+
+/// a window of the cards of a sheet
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CardsPage {
+    pub offset: usize,
+    pub cards: Vec<Card>,
+    /// how many cards the whole sheet makes
+    pub total: usize,
+    /// how many rows share their key with another row
+    #[serde(default)]
+    pub duplicates: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Kv {
     pub key: String,
     pub value: String,
+    /// the direction of the value, left to the browser when `None`
+    #[serde(default)]
+    pub dir: Option<Direction>,
+}
+
+/// the direction text is laid out in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum Direction {
+    Rtl,
+    Ltr,
+}
+
+impl Direction {
+    pub fn attr(self) -> &'static str {
+        match self {
+            Self::Rtl => "rtl",
+            Self::Ltr => "ltr",
+        }
+    }
+
+    /// the direction of the first letter of `text` with a strong direction,
+    /// `None` when it has only digits, punctuation or spaces
+    #[cfg(feature = "ssr")]
+    pub fn detect(text: &str) -> Option<Self> {
+        text.chars().find_map(|x| match x as u32 {
+            0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => Some(Self::Rtl),
+            _ if x.is_alphabetic() => Some(Self::Ltr),
+            _ => None,
+        })
+    }
 }
 
+/// the direction chosen for the values of a column instead of detecting it
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct CardsServerProps {
-    pub title_row_index: Option<NonZeroUsize>,
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ColumnDirection {
+    pub index: usize,
+    pub dir: Direction,
+}
+
+/// the transforms applied to the values of a column
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ColumnTransforms {
+    pub index: usize,
+    pub transforms: Transforms,
+}
+
+/// the changes made to a value before it is written on a card, in the
+/// order they are applied
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Transforms {
+    /// remove the arabic diacritics
+    #[serde(default)]
+    pub strip_tashkeel: bool,
+    /// remove the ـ stretching arabic words
+    #[serde(default)]
+    pub remove_tatweel: bool,
+    /// write أ إ آ ٱ as ا and ى as ي
+    #[serde(default)]
+    pub unify_letters: bool,
+    /// turn every run of spaces into a single space
+    #[serde(default)]
+    pub collapse_spaces: bool,
+    #[serde(default)]
+    pub trim: bool,
+    /// capitalize the first letter of every latin word and lower the others
+    #[serde(default)]
+    pub title_case: bool,
+    /// write the numbers without grouping their thousands, for ids and seat
+    /// numbers
+    #[serde(default)]
+    pub ungrouped: bool,
+    /// the most characters kept, the cut value ending with an ellipsis
+    #[serde(default)]
+    #[cfg_attr(feature = "ssr", schema(value_type = Option<usize>))]
+    pub max_length: Option<NonZeroUsize>,
+}
+
+impl Transforms {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// how the value of a merged region is spread over the cells it covers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum MergedCells {
+    /// only the top left cell of the region holds the value
+    #[default]
+    Keep,
+    /// every cell of the region holds the value
+    Fill,
+    /// like `Fill` and regions right above the title rows prefix the
+    /// headers under them as `group / header`
+    Prefix,
+}
+
+/// the part of a sheet the rows are read from
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum SheetArea {
+    /// the used range of the whole sheet
+    #[default]
+    Used,
+    /// a table defined in the sheet
+    Table(String),
+    /// a defined name referring to a range of the sheet
+    Named(String),
+}
+
+/// the kinds of files the rows can be read from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceKind {
+    Excel,
+    /// csv or tsv text read as a workbook of a single sheet
+    Delimited,
+}
+
+impl SourceKind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "xls" | "xlsx" | "xlsb" | "ods" => Some(Self::Excel),
+            "csv" | "tsv" | "txt" => Some(Self::Delimited),
+            _ => None,
+        }
+    }
+}
+
+/// how a delimited text file is decoded
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct TextOptions {
+    /// an encoding label like `windows-1256`, detected when empty
+    pub encoding: String,
+    /// the field delimiter, detected when `None`
+    #[cfg_attr(feature = "ssr", schema(value_type = Option<String>))]
+    pub delimiter: Option<char>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SheetSource {
+    #[cfg_attr(feature = "ssr", schema(value_type = String))]
     pub path: PathBuf,
     pub sheet: String,
+    /// only used for delimited text files
+    pub text: TextOptions,
+    pub area: SheetArea,
+    pub merged_cells: MergedCells,
+    /// skip hidden rows and columns and the rows excluded by the saved autofilter
+    pub skip_hidden: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CardsServerProps {
+    pub source: SheetSource,
+    #[cfg_attr(feature = "ssr", schema(value_type = Option<usize>))]
+    pub title_row_index: Option<NonZeroUsize>,
+    #[cfg_attr(feature = "ssr", schema(value_type = usize))]
+    pub title_rows_count: NonZeroUsize,
     pub columns_indexs: Vec<usize>,
+    #[serde(default)]
+    pub back: Option<CardBack>,
+    #[serde(default)]
+    pub layout: PageLayout,
+    #[serde(default)]
+    pub copies: Copies,
+    #[serde(default)]
+    pub columns_directions: Vec<ColumnDirection>,
+    #[serde(default)]
+    pub columns_transforms: Vec<ColumnTransforms>,
+    #[serde(default)]
+    pub format: ValueFormat,
+    #[serde(default)]
+    pub rules: Vec<ColumnRule>,
+    /// leave the rows breaking a rule out of the cards
+    #[serde(default)]
+    pub exclude_invalid: bool,
+    #[serde(default)]
+    pub duplicates: Duplicates,
+    #[serde(default)]
+    pub maps: Vec<ColumnMap>,
 }
 
-#[server]
-async fn get_cards(reqs: CardsServerProps) -> Result<Vec<Card>, ServerFnError> {
-    use calamine::{Data, DeError, RangeDeserializerBuilder, Reader, Xlsx, open_workbook};
-
-    let CardsServerProps {
-        title_row_index,
-        path,
-        sheet,
-        columns_indexs,
-    } = reqs;
-    let mut workbook: Xlsx<_> = open_workbook(&path)?;
-    let range = workbook.worksheet_range(&sheet)?;
-
-    let mut iter = RangeDeserializerBuilder::new()
-        .has_headers(false)
-        .from_range(&range)?;
-
-    let headers = match title_row_index {
-        Some(i) => iter
-            .nth(Into::<usize>::into(i) - 1)
-            .unwrap_or(Err(DeError::HeaderNotFound(format!(
-                "Error number {i} should contain headers"
-            ))))?,
-        None => iter
-            .next()
-            .unwrap_or(Err(DeError::HeaderNotFound(String::from(
-                "Error : first row should contain headers",
-            ))))?,
-    };
+impl CardsServerProps {
+    /// whether the rows are checked by rules, maps or a column of copies
+    pub fn has_checks(&self) -> bool {
+        !self.rules.is_empty() || !self.maps.is_empty() || matches!(self.copies, Copies::Column(_))
+    }
+
+    /// every file the cards are read from, the sheet and the value maps
+    #[cfg(feature = "ssr")]
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        let maps = self.maps.iter().filter_map(|x| match &x.map {
+            ValueMap::Inline(_) => None,
+            ValueMap::Sheet(source) => Some(source.path.as_path()),
+        });
+        std::iter::once(self.source.path.as_path()).chain(maps)
+    }
+}
+
+/// the labels written on the cards instead of the values of a column
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ColumnMap {
+    pub index: usize,
+    pub map: ValueMap,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum ValueMap {
+    Inline(Vec<MapEntry>),
+    /// the values in the first column of a sheet or a csv file and their
+    /// labels in the second, under a title row
+    Sheet(SheetSource),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct MapEntry {
+    pub value: String,
+    pub label: String,
+}
+
+/// a check on the values of one column
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ColumnRule {
+    pub index: usize,
+    pub rule: Rule,
+}
+
+/// what every value of a column must be, empty values only break `Required`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum Rule {
+    Required,
+    /// a regular expression matching the whole value
+    Regex(String),
+    /// bounds on the count of characters
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// bounds on the number the value holds
+    Range {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// no other row holds the same value
+    Unique,
+    Allowed(Vec<String>),
+    /// the map of the column has a label for the value, checked on every
+    /// column with a map
+    Mapped,
+    /// a count of copies of at most `MAX_COPIES`, checked on the column of
+    /// copies
+    Copies,
+}
+
+impl Rule {
+    /// the message key naming the rule
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Required => "rule-required",
+            Self::Regex(_) => "rule-regex",
+            Self::Length { .. } => "rule-length",
+            Self::Range { .. } => "rule-range",
+            Self::Unique => "rule-unique",
+            Self::Allowed(_) => "rule-allowed",
+            Self::Mapped => "rule-mapped",
+            Self::Copies => "rule-copies",
+        }
+    }
 
-    let mut cards = Vec::new();
-    for (i, row) in iter.enumerate() {
-        let mut kvs = Vec::new();
-        let row: Vec<Data> = row?;
-        for index in columns_indexs.iter() {
-            let header = headers[*index].to_string();
-            let value = row[*index].to_string();
-            if !header.is_empty() && !value.is_empty() {
-                kvs.push(Kv {
-                    key: header,
-                    value: value,
-                });
+    /// the settings of the rule as written in the form
+    pub fn detail(&self) -> String {
+        let bounds = |min: Option<String>, max: Option<String>| {
+            format!("{}..{}", min.unwrap_or_default(), max.unwrap_or_default())
+        };
+        match self {
+            Self::Required | Self::Unique | Self::Mapped => String::new(),
+            Self::Regex(x) => x.clone(),
+            Self::Length { min, max } => {
+                bounds(min.map(|x| x.to_string()), max.map(|x| x.to_string()))
             }
+            Self::Range { min, max } => {
+                bounds(min.map(|x| x.to_string()), max.map(|x| x.to_string()))
+            }
+            Self::Allowed(x) => x.join(", "),
+            Self::Copies => format!("..{MAX_COPIES}"),
+        }
+    }
+}
+
+/// a cell breaking a rule
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Violation {
+    pub row_index: usize,
+    /// the number of the row in the spreadsheet starting from 1
+    pub sheet_row: usize,
+    pub column: String,
+    pub value: String,
+    pub rule: Rule,
+}
+
+/// how rows holding the same key are found and which of them make cards
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Duplicates {
+    /// the columns making the key of a row, no row is a duplicate when empty
+    pub columns_indexs: Vec<usize>,
+    #[serde(default)]
+    pub matching: KeyMatching,
+    #[serde(default)]
+    pub keep: KeepDuplicates,
+}
+
+/// when the keys of two rows are the same
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum KeyMatching {
+    /// the values are the same once trimmed
+    #[default]
+    Exact,
+    /// the values are the same once trimmed, case folded, their spaces
+    /// collapsed and أ إ آ written ا and ة written ه
+    Normalized,
+}
+
+/// which rows of a set of duplicates make cards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum KeepDuplicates {
+    #[default]
+    All,
+    /// the highest row
+    First,
+    /// the lowest row
+    Last,
+}
+
+/// how the numbers and dates of the sheet are written on the cards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ValueFormat {
+    #[serde(default)]
+    pub digits: Digits,
+    #[serde(default)]
+    pub date: DateLocale,
+    #[serde(default)]
+    pub calendar: Calendar,
+    /// group the digits of whole numbers by thousands
+    #[serde(default)]
+    pub thousands: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum Digits {
+    /// 0123456789
+    #[default]
+    Western,
+    /// ٠١٢٣٤٥٦٧٨٩
+    ArabicIndic,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum DateLocale {
+    /// the value as the sheet stores it
+    #[default]
+    AsStored,
+    /// year/month/day
+    Numeric,
+    /// day, arabic month name and year
+    Arabic,
+    /// day, english month name and year
+    English,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum Calendar {
+    #[default]
+    Gregorian,
+    /// the tabular islamic calendar, which can be a day off the sighted month
+    Hijri,
+}
+
+/// how many cards every row makes
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum Copies {
+    #[cfg_attr(feature = "ssr", schema(value_type = usize))]
+    Fixed(NonZeroUsize),
+    /// the index of the column holding the count of each row, rows without
+    /// a number in it make one card
+    Column(usize),
+}
+
+impl Default for Copies {
+    fn default() -> Self {
+        Self::Fixed(NonZeroUsize::MIN)
+    }
+}
+
+/// the back side printed behind every card of a set
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CardBack {
+    /// free text like rules or a return address, `{header}` is replaced by
+    /// the value of the row under that header
+    pub text: String,
+    pub columns_indexs: Vec<usize>,
+}
+
+/// how the cards are laid out on printed pages
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "LayoutFields")]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PageLayout {
+    #[cfg_attr(feature = "ssr", schema(value_type = usize))]
+    pub columns: NonZeroUsize,
+    /// rows of cards on a page, the cards flow over the pages when `None`.
+    /// required with a back, which only lines up with its front when it is set
+    #[cfg_attr(feature = "ssr", schema(value_type = Option<usize>))]
+    pub rows: Option<NonZeroUsize>,
+    /// positions left blank at the start of the first page, for label sheets
+    /// already partly used
+    #[serde(default)]
+    pub skip: usize,
+    /// the direction of the cards, the one of the page when `None`
+    #[serde(default)]
+    pub dir: Option<Direction>,
+}
+
+/// the fields of a page layout as written, checked before they make one
+#[derive(Deserialize)]
+struct LayoutFields {
+    columns: NonZeroUsize,
+    rows: Option<NonZeroUsize>,
+    #[serde(default)]
+    skip: usize,
+    #[serde(default)]
+    dir: Option<Direction>,
+}
+
+impl TryFrom<LayoutFields> for PageLayout {
+    type Error = String;
+
+    fn try_from(fields: LayoutFields) -> Result<Self, String> {
+        let layout = Self {
+            columns: fields.columns,
+            rows: fields.rows,
+            skip: fields.skip,
+            dir: fields.dir,
+        };
+        layout.check()?;
+        Ok(layout)
+    }
+}
+
+impl Default for PageLayout {
+    fn default() -> Self {
+        Self {
+            columns: NonZeroUsize::new(3).unwrap(),
+            rows: None,
+            skip: 0,
+            dir: None,
+        }
+    }
+}
+
+impl PageLayout {
+    /// the css grid of the cards of a page
+    pub fn grid_style(&self) -> String {
+        let columns = format!(
+            "grid-template-columns: repeat({}, minmax(0, 1fr));",
+            self.columns
+        );
+        match self.rows {
+            // equal rows over the whole page keep blank positions at their size
+            Some(rows) => {
+                format!(
+                    "{columns} grid-template-rows: repeat({rows}, minmax(0, 1fr)); height: 100vh;"
+                )
+            }
+            None => columns,
+        }
+    }
+
+    /// blank positions on the first page, never a whole page nor a whole
+    /// row of cards flowing over the pages
+    pub fn skipped(&self) -> usize {
+        let blank = match self.rows {
+            Some(rows) => rows.get().saturating_mul(self.columns.get()),
+            None => self.columns.get(),
+        };
+        self.skip.min(blank - 1)
+    }
+
+    /// refuses a page larger than any printed one, which would only fill
+    /// the memory with blank positions
+    pub fn check(&self) -> Result<(), String> {
+        if self.columns.get() > MAX_PAGE_COLUMNS {
+            return Err(format!(
+                "{} columns of cards are more than {MAX_PAGE_COLUMNS}",
+                self.columns
+            ));
+        }
+        if let Some(rows) = self.rows.filter(|x| x.get() > MAX_PAGE_ROWS) {
+            return Err(format!(
+                "{rows} rows of cards are more than {MAX_PAGE_ROWS}"
+            ));
+        }
+        if self.skip >= MAX_PAGE_COLUMNS * MAX_PAGE_ROWS {
+            return Err(format!(
+                "{} skipped positions are more than a page",
+                self.skip
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[server]
+async fn get_cards(
+    reqs: CardsServerProps,
+    offset: usize,
+    limit: usize,
+) -> Result<CardsPage, ServerFnError> {
+    let user = crate::auth::current_user()?;
+    let (path, sheet) = (reqs.source.path.clone(), reqs.source.sheet.clone());
+    let checked = reqs.paths().try_for_each(|x| user.check_path(x));
+    let page = checked.and_then(|_| super::sheet::build_cards(reqs, offset, Some(limit)));
+    super::audit::record("get_cards", &path, Some(&sheet), &page, |x| {
+        Some(x.cards.len())
+    })?;
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(count: usize, back: bool) -> Vec<Card> {
+        (0..count)
+            .map(|row_index| Card {
+                row_index,
+                sheet_row: row_index + 2,
+                kv: Vec::new(),
+                back: back.then(|| CardSide {
+                    text: String::new(),
+                    kv: Vec::new(),
+                }),
+                copy: 1,
+                copies: 1,
+                duplicate: false,
+            })
+            .collect()
+    }
+
+    fn layout(columns: usize, rows: Option<usize>, skip: usize) -> PageLayout {
+        PageLayout {
+            columns: NonZeroUsize::new(columns).unwrap(),
+            rows: rows.and_then(NonZeroUsize::new),
+            skip,
+            dir: None,
         }
-        cards.push(Card {
-            row_index: i,
-            kv: kvs,
-        });
     }
 
-    Ok(cards)
+    fn sides(pages: &[Page]) -> Vec<(bool, Vec<Option<usize>>)> {
+        pages.iter().map(|x| (x.back, x.slots.clone())).collect()
+    }
+
+    #[test]
+    fn backs_mirrored_behind_fronts() {
+        let pages = print_pages(&cards(5, true), &layout(2, Some(2), 1));
+        assert_eq!(
+            sides(&pages),
+            [
+                (false, vec![None, Some(0), Some(1), Some(2)]),
+                (true, vec![Some(0), None, Some(2), Some(1)]),
+                (false, vec![Some(3), Some(4)]),
+                (true, vec![Some(4), Some(3)]),
+            ]
+        );
+        // the short last row keeps its back on the mirrored side
+        let pages = print_pages(&cards(3, true), &layout(2, Some(2), 0));
+        assert_eq!(
+            sides(&pages),
+            [
+                (false, vec![Some(0), Some(1), Some(2), None]),
+                (true, vec![Some(1), Some(0), None, Some(2)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn fronts_only() {
+        // flowing cards never skip a whole row
+        let pages = print_pages(&cards(5, false), &layout(2, None, 2));
+        assert_eq!(
+            sides(&pages),
+            [(
+                false,
+                vec![None, Some(0), Some(1), Some(2), Some(3), Some(4)]
+            )]
+        );
+        assert_eq!(
+            print_pages(&cards(5, false), &layout(2, Some(1), 0)).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn templates() {
+        let value_of = |name: &str| match name {
+            "copy" => Some("2".to_string()),
+            "name" => Some("{copy}".to_string()),
+            _ => None,
+        };
+        assert_eq!(fill_template("{copy} of 3", value_of), "2 of 3");
+        assert_eq!(fill_template("{ copy }/{missing}", value_of), "2/{missing}");
+        // values are never filled again and unclosed braces are kept
+        assert_eq!(fill_template("{name} {copy", value_of), "{copy} {copy");
+        assert_eq!(fill_template("{{copy}}", value_of), "{2}");
+        assert_eq!(fill_template("", value_of), "");
+    }
+
+    #[test]
+    fn batches_end_on_full_rows() {
+        assert!((1..=MAX_PAGE_COLUMNS).all(|x| PRINT_BATCH.is_multiple_of(x)));
+    }
+
+    #[test]
+    fn skipped_positions() {
+        assert_eq!(layout(3, Some(2), 4).skipped(), 4);
+        assert_eq!(layout(3, Some(2), 10).skipped(), 5);
+        assert_eq!(layout(3, None, 10).skipped(), 2);
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn oversized_layouts() {
+        let read = |x: &str| serde_json::from_str::<PageLayout>(x);
+        assert!(read(r#"{"columns": 3, "rows": 10, "skip": 4}"#).is_ok());
+        assert!(read(r#"{"columns": 1000000, "rows": null}"#).is_err());
+        assert!(read(r#"{"columns": 3, "rows": 1000000}"#).is_err());
+        assert!(read(r#"{"columns": 3, "rows": null, "skip": 10000000000}"#).is_err());
+    }
 }
@@ -0,0 +1,108 @@
+use super::{CardsServerProps, Violation};
+use crate::app::i18n::{message, tr, use_lang};
+use leptos::prelude::*;
+use std::collections::HashSet;
+
+/// the cells breaking the rules of the card set, holding the cards back
+/// until they are reviewed unless the invalid rows are left out
+#[component]
+pub fn ValidationReport(
+    csp: CardsServerProps,
+    revision: RwSignal<usize>,
+    editing: RwSignal<bool>,
+    reviewed: RwSignal<bool>,
+) -> impl IntoView {
+    let exclude_invalid = csp.exclude_invalid;
+    let report_res = Resource::new(move || revision.get(), move |_| validate_cards(csp.clone()));
+    Effect::new(move || {
+        if let Some(Ok(violations)) = report_res.get() {
+            reviewed.set(exclude_invalid || violations.is_empty());
+        }
+    });
+    let lang = use_lang();
+    let report = move || {
+        report_res.get().map(|report| match report {
+            Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_any(),
+            Ok(violations) if violations.is_empty() => ().into_any(),
+            Ok(violations) => {
+                let rows = violations
+                    .iter()
+                    .map(|x| x.row_index)
+                    .collect::<HashSet<_>>()
+                    .len()
+                    .to_string();
+                let count = violations.len().to_string();
+                view! {
+                    <div class="border-2 border-red-500 rounded-lg p-2 m-2 print:hidden">
+                        <p class="font-bold">{move || message(
+                            lang.get(),
+                            "violations-count",
+                            &[("count", &count), ("rows", &rows)],
+                        )}</p>
+                        <Show when=move || exclude_invalid>
+                            <p>{tr("violations-excluded")}</p>
+                        </Show>
+                        <table class="w-full text-sm border-collapse">
+                            <thead>
+                                <tr class="border-b-2">
+                                    <th class="p-2">{tr("row-number")}</th>
+                                    <th class="p-2">{tr("violation-column")}</th>
+                                    <th class="p-2">{tr("violation-value")}</th>
+                                    <th class="p-2">{tr("violation-rule")}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {violations.into_iter().map(|x| view! {
+                                    <tr class="border-b">
+                                        <td class="p-2">{x.sheet_row}</td>
+                                        <td class="p-2"><bdi>{x.column}</bdi></td>
+                                        <td class="p-2"><bdi>{x.value}</bdi></td>
+                                        <td class="p-2">
+                                            {tr(x.rule.label())}
+                                            " "
+                                            <bdi dir="ltr">{x.rule.detail()}</bdi>
+                                        </td>
+                                    </tr>
+                                }).collect_view()}
+                            </tbody>
+                        </table>
+                        <Show when=move || !reviewed.get()>
+                            <div class="flex gap-4 m-2">
+                                <button
+                                    on:click=move |_| reviewed.set(true)
+                                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                                >{tr("show-cards-anyway")}</button>
+                                <button
+                                    on:click=move |_| editing.set(true)
+                                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                                >{tr("edit")}</button>
+                            </div>
+                        </Show>
+                    </div>
+                }
+                .into_any()
+            }
+        })
+    };
+    view! { <Suspense>{report}</Suspense> }
+}
+
+#[server]
+async fn validate_cards(reqs: CardsServerProps) -> Result<Vec<Violation>, ServerFnError> {
+    let user = crate::auth::current_user()?;
+    if !reqs.has_checks() {
+        return Ok(Vec::new());
+    }
+    let violations = reqs
+        .paths()
+        .try_for_each(|x| user.check_path(x))
+        .and_then(|_| crate::app::sheet::validate_rows(&reqs));
+    crate::app::audit::record(
+        "validate_cards",
+        &reqs.source.path,
+        Some(&reqs.source.sheet),
+        &violations,
+        |x| Some(x.len()),
+    )?;
+    violations
+}
@@ -0,0 +1,318 @@
+use super::{Card, PrintedRow};
+use crate::app::i18n::{message, tr, use_lang};
+use leptos::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+/// which cards are shown according to their print history
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PrintedFilter {
+    All,
+    /// hide the cards printed with their current content
+    HidePrinted,
+    /// show only the cards changed since they were printed
+    OnlyChanged,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PrintState {
+    New,
+    Printed,
+    /// printed before the row was edited
+    Changed,
+}
+
+/// the cards chosen for the next print run and the ones printed before,
+/// both keyed by the row index or the value of the chosen id column
+#[derive(Clone, Copy)]
+pub struct Selection {
+    pub selected: RwSignal<HashSet<String>>,
+    /// the digest of every printed card by its key
+    pub printed: RwSignal<HashMap<String, u64>>,
+    /// the key of the cards column that identifies them, the row index when `None`
+    pub id_column: RwSignal<Option<String>>,
+    pub only_selected: RwSignal<bool>,
+    pub printed_filter: RwSignal<PrintedFilter>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self {
+            selected: RwSignal::new(HashSet::new()),
+            printed: RwSignal::new(HashMap::new()),
+            id_column: RwSignal::new(None),
+            only_selected: RwSignal::new(false),
+            printed_filter: RwSignal::new(PrintedFilter::All),
+        }
+    }
+
+    /// fills `printed` from the history of `project` made from the file at
+    /// `path` on the server
+    pub fn load_history(&self, project: String, path: PathBuf) {
+        let printed = self.printed;
+        leptos::task::spawn_local(async move {
+            if let Ok(rows) = print_history(project, path).await {
+                printed.set(rows.into_iter().map(|x| (x.id, x.digest)).collect());
+            }
+        });
+    }
+
+    pub fn key(&self, card: &Card) -> String {
+        self.id_column
+            .read()
+            .as_ref()
+            .and_then(|id| card.kv.iter().find(|x| x.key == *id))
+            .map(|x| x.value.clone())
+            .unwrap_or_else(|| (card.row_index + 1).to_string())
+    }
+
+    pub fn toggle(&self, card: &Card) {
+        let key = self.key(card);
+        self.selected.update(|x| {
+            if !x.remove(&key) {
+                x.insert(key);
+            }
+        });
+    }
+
+    pub fn is_selected(&self, card: &Card) -> bool {
+        self.selected.read().contains(&self.key(card))
+    }
+
+    pub fn print_state(&self, card: &Card) -> PrintState {
+        match self.printed.read().get(&self.key(card)) {
+            None => PrintState::New,
+            Some(digest) if *digest == card.digest() => PrintState::Printed,
+            Some(_) => PrintState::Changed,
+        }
+    }
+
+    pub fn is_hidden(&self, card: &Card) -> bool {
+        match self.printed_filter.get() {
+            PrintedFilter::All => false,
+            PrintedFilter::HidePrinted => self.print_state(card) == PrintState::Printed,
+            PrintedFilter::OnlyChanged => self.print_state(card) != PrintState::Changed,
+        }
+    }
+
+    /// the card is left out of the printed page
+    pub fn is_skipped(&self, card: &Card) -> bool {
+        self.is_hidden(card) || (self.only_selected.get() && !self.is_selected(card))
+    }
+}
+
+#[component]
+pub fn SelectionBar(
+    cards: RwSignal<Vec<Card>>,
+    /// the cards laid out on the printed pages, none while loading
+    printable: Signal<Vec<Card>>,
+    loading: RwSignal<bool>,
+    selection: Selection,
+    project: String,
+    path: PathBuf,
+) -> impl IntoView {
+    let from = RwSignal::new(String::new());
+    let to = RwSignal::new(String::new());
+    let search = RwSignal::new(String::new());
+    // the cards of the last print run, recorded once the user confirms
+    // they came out of the printer, as a cancelled dialog looks the same
+    let unconfirmed = RwSignal::new(None::<Vec<PrintedRow>>);
+    let lang = use_lang();
+
+    let columns = move || {
+        cards.with(|x| {
+            x.first()
+                .map(|card| card.kv.iter().map(|x| x.key.clone()).collect::<Vec<_>>())
+                .unwrap_or_default()
+        })
+    };
+    let select = move |matches: &dyn Fn(&Card) -> bool| {
+        let keys = cards.with(|x| {
+            x.iter()
+                .filter(|card| matches(card))
+                .map(|card| selection.key(card))
+                .collect::<Vec<_>>()
+        });
+        selection.selected.update(|x| x.extend(keys));
+    };
+    let select_range = move |_| {
+        let from = from.get_untracked().trim().parse::<usize>().unwrap_or(1);
+        let to = to
+            .get_untracked()
+            .trim()
+            .parse::<usize>()
+            .unwrap_or(usize::MAX);
+        select(&|card| (from..=to).contains(&card.sheet_row));
+    };
+    let select_found = move |_| {
+        let search = search.get_untracked().trim().to_lowercase();
+        if search.is_empty() {
+            return;
+        }
+        select(&|card| {
+            card.kv
+                .iter()
+                .any(|x| x.value.to_lowercase().contains(&search))
+        });
+    };
+    let print = move |_| {
+        let rows = printable.with_untracked(|x| {
+            x.iter()
+                .map(|card| PrintedRow {
+                    id: selection.key(card),
+                    digest: card.digest(),
+                })
+                .collect::<Vec<_>>()
+        });
+        if rows.is_empty() {
+            return;
+        }
+        let _ = window().print();
+        unconfirmed.set(Some(rows));
+    };
+    let confirm = move |_| {
+        let Some(rows) = unconfirmed.get_untracked() else {
+            return;
+        };
+        unconfirmed.set(None);
+        selection
+            .printed
+            .update(|x| x.extend(rows.iter().map(|x| (x.id.clone(), x.digest))));
+        let (project, path) = (project.clone(), path.clone());
+        leptos::task::spawn_local(async move {
+            let _ = record_print(project, path, rows).await;
+        });
+    };
+
+    view! {
+        <div class="flex flex-wrap gap-4 items-center print:hidden">
+            <label class="flex gap-2 items-center">
+                {tr("id-column")}
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| {
+                        let value = ev.target().value();
+                        selection.id_column.set(Some(value).filter(|x| !x.is_empty()));
+                        selection.selected.update(|x| x.clear());
+                    }
+                >
+                    <option value="">{tr("row-number")}</option>
+                    {
+                        move || columns().into_iter().map(|column| view! {
+                            <option
+                                value=column.clone()
+                                selected=move || selection.id_column.read().as_ref() == Some(&column)
+                            >{column.clone()}</option>
+                        }).collect_view()
+                    }
+                </select>
+            </label>
+            <div class="flex gap-2 items-center">
+                <input
+                    type="number"
+                    min="1"
+                    placeholder=tr("from")
+                    class="border-2 rounded-lg p-2 w-24"
+                    on:input:target=move |ev| from.set(ev.target().value())
+                />
+                <input
+                    type="number"
+                    min="1"
+                    placeholder=tr("to")
+                    class="border-2 rounded-lg p-2 w-24"
+                    on:input:target=move |ev| to.set(ev.target().value())
+                />
+                <button
+                    on:click=select_range
+                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                >{tr("select-rows")}</button>
+            </div>
+            <div class="flex gap-2 items-center">
+                <input
+                    type="search"
+                    placeholder=tr("search")
+                    class="border-2 rounded-lg p-2"
+                    on:input:target=move |ev| search.set(ev.target().value())
+                />
+                <button
+                    on:click=select_found
+                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                >{tr("select-found")}</button>
+            </div>
+            <button
+                on:click=move |_| selection.selected.update(|x| x.clear())
+                class="border-2 rounded-lg p-2 hover:cursor-pointer"
+            >{tr("clear-selection")}</button>
+            <span>{move || {
+                let count = selection.selected.read().len().to_string();
+                message(lang.get(), "selected-count", &[("count", &count)])
+            }}</span>
+            <label class="flex gap-2 items-center">
+                <input
+                    type="checkbox"
+                    class="w-5 h-5"
+                    prop:checked=move || selection.only_selected.get()
+                    on:change:target=move |ev| selection.only_selected.set(ev.target().checked())
+                />
+                {tr("print-selected-only")}
+            </label>
+            <select
+                class="border-2 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    let filter = match ev.target().value().as_str() {
+                        "printed" => PrintedFilter::HidePrinted,
+                        "changed" => PrintedFilter::OnlyChanged,
+                        _ => PrintedFilter::All,
+                    };
+                    selection.printed_filter.set(filter);
+                }
+            >
+                <option value="">{tr("all-cards")}</option>
+                <option value="printed">{tr("hide-printed")}</option>
+                <option value="changed">{tr("only-changed")}</option>
+            </select>
+            <button
+                on:click=print
+                disabled=move || loading.get()
+                class="text-xl font-bold border-2 rounded-xl p-2 m-2 hover:cursor-pointer disabled:cursor-wait disabled:opacity-50"
+            >{tr("print")}</button>
+        </div>
+        {move || unconfirmed.with(|rows| rows.as_ref().map(|rows| {
+            let count = rows.len().to_string();
+            view! {
+                <div class="flex gap-4 items-center bg-sky-100 border-2 border-sky-500 rounded-lg p-2 m-2 print:hidden">
+                    <span class="grow">{move || message(lang.get(), "print-confirm", &[("count", &count)])}</span>
+                    <button
+                        on:click=confirm.clone()
+                        class="border-2 rounded-lg px-2 hover:cursor-pointer"
+                    >{tr("print-done")}</button>
+                    <button
+                        on:click=move |_| unconfirmed.set(None)
+                        class="border-2 rounded-lg px-2 hover:cursor-pointer"
+                    >{tr("print-failed")}</button>
+                </div>
+            }
+        }))}
+    }
+}
+
+#[server]
+async fn record_print(
+    project: String,
+    path: PathBuf,
+    rows: Vec<PrintedRow>,
+) -> Result<(), ServerFnError> {
+    use crate::app::history::{self, EventKind};
+    let user = crate::auth::current_user()?;
+    user.check_path(&path)?;
+    history::record(EventKind::Print, &user, project, path, rows)
+}
+
+#[server]
+async fn print_history(project: String, path: PathBuf) -> Result<Vec<PrintedRow>, ServerFnError> {
+    let user = crate::auth::current_user()?;
+    user.check_path(&path)?;
+    crate::app::history::last_prints(&project, &path, &user)
+}
@@ -0,0 +1,106 @@
+//! every print or export of cards appended as a json line, read back to tell
+//! which rows of a project were printed and with what content
+
+use super::cards::PrintedRow;
+use crate::{auth::User, config::config};
+use leptos::prelude::ServerFnError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// the history file in the data directory
+const HISTORY_FILE: &str = "print_history.jsonl";
+
+/// keeps the lines of concurrent events from interleaving
+static WRITING: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Print,
+    Export,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PrintEvent {
+    kind: EventKind,
+    /// the user who printed
+    #[serde(default)]
+    owner: String,
+    project: String,
+    path: PathBuf,
+    /// modification time and size of the file when it was printed
+    fingerprint: String,
+    /// seconds since the unix epoch
+    timestamp: u64,
+    rows: Vec<PrintedRow>,
+}
+
+fn fingerprint(path: &Path) -> Result<String, ServerFnError> {
+    let metadata = std::fs::metadata(path)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_secs();
+    Ok(format!("{modified}-{}", metadata.len()))
+}
+
+pub fn record(
+    kind: EventKind,
+    user: &User,
+    project: String,
+    path: PathBuf,
+    rows: Vec<PrintedRow>,
+) -> Result<(), ServerFnError> {
+    let event = PrintEvent {
+        kind,
+        owner: user.name.clone(),
+        project,
+        fingerprint: fingerprint(&path)?,
+        path,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        rows,
+    };
+    let mut line = serde_json::to_string(&event)?;
+    line.push('\n');
+
+    let _writing = WRITING.lock().unwrap_or_else(|x| x.into_inner());
+    let path = config().data_dir.join(HISTORY_FILE);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// the last print of every row of `project` from the file at `path` the user
+/// may see, another file under the same title has a history of its own
+pub fn last_prints(
+    project: &str,
+    path: &Path,
+    user: &User,
+) -> Result<Vec<PrintedRow>, ServerFnError> {
+    let file = match std::fs::File::open(config().data_dir.join(HISTORY_FILE)) {
+        Ok(x) => x,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut rows = HashMap::new();
+    for line in BufReader::new(file).lines() {
+        // a line cut by a crash is skipped instead of losing the whole history
+        let Ok(event) = serde_json::from_str::<PrintEvent>(&line?) else {
+            continue;
+        };
+        if event.project != project || event.path != path || !user.sees(&event.owner) {
+            continue;
+        }
+        for row in event.rows {
+            rows.insert(row.id.clone(), row);
+        }
+    }
+    Ok(rows.into_values().collect())
+}
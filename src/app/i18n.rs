@@ -0,0 +1,157 @@
+//! the ui strings of every language read from the message catalogs under
+//! `locales`, one `key = value` per line in the manner of fluent
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::LazyLock};
+
+/// the cookie remembering the chosen language
+const COOKIE: &str = "lang";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Lang {
+    #[default]
+    Ar,
+    En,
+}
+
+impl Lang {
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Ar => "ar",
+            Self::En => "en",
+        }
+    }
+
+    pub fn dir(self) -> &'static str {
+        match self {
+            Self::Ar => "rtl",
+            Self::En => "ltr",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim() {
+            "ar" => Some(Self::Ar),
+            "en" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Self::Ar => Self::En,
+            Self::En => Self::Ar,
+        }
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Self::Ar => include_str!("../../locales/ar.ftl"),
+            Self::En => include_str!("../../locales/en.ftl"),
+        }
+    }
+}
+
+static CATALOGS: LazyLock<HashMap<(Lang, &'static str), &'static str>> = LazyLock::new(|| {
+    [Lang::Ar, Lang::En]
+        .into_iter()
+        .flat_map(|lang| {
+            lang.catalog()
+                .lines()
+                .filter(|x| !x.trim().is_empty() && !x.starts_with('#'))
+                .filter_map(|x| x.split_once(" = "))
+                .map(move |(key, value)| ((lang, key.trim()), value.trim()))
+        })
+        .collect()
+});
+
+/// the message `key` of `lang` with every `{ $name }` replaced by its argument,
+/// falling back to arabic and then to the key itself
+pub fn message(lang: Lang, key: &str, args: &[(&str, &str)]) -> String {
+    let mut text = CATALOGS
+        .get(&(lang, key))
+        .or_else(|| CATALOGS.get(&(Lang::Ar, key)))
+        .map_or_else(|| key.to_string(), |x| x.to_string());
+    for (name, value) in args {
+        text = text.replace(&format!("{{ ${name} }}"), value);
+    }
+    text
+}
+
+#[derive(Clone, Copy)]
+struct Locale(RwSignal<Lang>);
+
+/// the language of the ui, arabic outside of the app
+pub fn use_lang() -> Signal<Lang> {
+    use_context::<Locale>()
+        .map(|x| x.0.into())
+        .unwrap_or_else(|| Signal::stored(Lang::default()))
+}
+
+/// the message `key` in the language of the ui, following its changes
+pub fn tr(key: &'static str) -> impl Fn() -> String + Copy + Send + Sync + 'static {
+    let lang = use_lang();
+    move || message(lang.get(), key, &[])
+}
+
+/// the language remembered in the cookie of the request on the server, or
+/// the one the server rendered on the client
+fn initial_lang() -> Lang {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::http::{header::COOKIE as COOKIE_HEADER, request::Parts};
+        let lang = use_context::<Parts>().and_then(|parts| {
+            let cookies = parts.headers.get(COOKIE_HEADER)?.to_str().ok()?;
+            cookies
+                .split(';')
+                .filter_map(|x| x.trim().split_once('='))
+                .find(|(name, _)| *name == COOKIE)
+                .and_then(|(_, value)| Lang::from_code(value))
+        });
+        lang.unwrap_or(crate::config::config().language)
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        document()
+            .document_element()
+            .and_then(|x| x.get_attribute("lang"))
+            .and_then(|x| Lang::from_code(&x))
+            .unwrap_or_default()
+    }
+}
+
+/// makes the language of the request the language of the ui
+pub fn provide_locale() -> RwSignal<Lang> {
+    let lang = RwSignal::new(initial_lang());
+    provide_context(Locale(lang));
+    lang
+}
+
+#[component]
+pub fn LanguageSwitch() -> impl IntoView {
+    let locale = use_context::<Locale>();
+    let lang = use_lang();
+    let switch = move |_| {
+        let Some(Locale(lang)) = locale else {
+            return;
+        };
+        let next = lang.get_untracked().other();
+        lang.set(next);
+        let cookie = format!("{COOKIE}={}; path=/; max-age=31536000", next.code());
+        #[cfg(feature = "hydrate")]
+        {
+            use wasm_bindgen::JsCast;
+            let document = document().unchecked_into::<web_sys::HtmlDocument>();
+            let _ = document.set_cookie(&cookie);
+        }
+        #[cfg(not(feature = "hydrate"))]
+        let _ = cookie;
+    };
+    view! {
+        <button
+            on:click=switch
+            class="text-xs p-3 hover:cursor-pointer print:hidden"
+        >{move || message(lang.get(), "other-language", &[])}</button>
+    }
+}
@@ -0,0 +1,87 @@
+use super::i18n::tr;
+use leptos::prelude::*;
+
+#[component]
+pub fn LoginPage() -> impl IntoView {
+    let login = ServerAction::<Login>::new();
+    let failed = move || matches!(login.value().get(), Some(Err(_)));
+    view! {
+        <ActionForm action=login>
+            <dl class="border-sky-500 border-5 rounded-xl p-2 m-2 text-xl text-center max-w-md mx-auto">
+                <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("user-name")}</dd>
+                <dt>
+                    <input
+                        type="text"
+                        name="name"
+                        required
+                        autocomplete="username"
+                        class="border-2 rounded-lg p-2 text-center"
+                    />
+                </dt>
+                <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("password")}</dd>
+                <dt>
+                    <input
+                        type="password"
+                        name="password"
+                        required
+                        autocomplete="current-password"
+                        class="border-2 rounded-lg p-2 text-center"
+                    />
+                </dt>
+                <Show when=failed>
+                    <p class="text-red-600 m-2">{tr("login-failed")}</p>
+                </Show>
+                <button
+                    type="submit"
+                    class="text-3xl font-bold border-2 rounded-xl p-4 m-2 hover:cursor-pointer"
+                >{tr("login")}</button>
+            </dl>
+        </ActionForm>
+    }
+}
+
+#[component]
+pub fn LogoutButton() -> impl IntoView {
+    let logout = ServerAction::<Logout>::new();
+    view! {
+        <ActionForm action=logout attr:class="print:hidden">
+            <button
+                type="submit"
+                class="text-xs p-3 hover:cursor-pointer"
+            >{tr("logout")}</button>
+        </ActionForm>
+    }
+}
+
+#[server(endpoint = "login")]
+async fn login(name: String, password: String) -> Result<(), ServerFnError> {
+    use crate::auth;
+    use axum::http::{HeaderValue, header::SET_COOKIE};
+
+    let Some(token) = auth::login(&name, &password).await? else {
+        return Err(ServerFnError::new("wrong name or password"));
+    };
+    let response = expect_context::<leptos_axum::ResponseOptions>();
+    response.insert_header(
+        SET_COOKIE,
+        HeaderValue::from_str(&auth::session_cookie(&token))?,
+    );
+    leptos_axum::redirect("/");
+    Ok(())
+}
+
+#[server(endpoint = "logout")]
+async fn logout() -> Result<(), ServerFnError> {
+    use crate::auth;
+    use axum::http::{HeaderValue, header::SET_COOKIE, request::Parts};
+
+    if let Some(parts) = use_context::<Parts>()
+        && let Some(token) = auth::request_token(&parts.headers)
+    {
+        auth::logout(token);
+    }
+    let response = expect_context::<leptos_axum::ResponseOptions>();
+    response.insert_header(SET_COOKIE, HeaderValue::from_str(&auth::cleared_cookie())?);
+    leptos_axum::redirect("/login");
+    Ok(())
+}
@@ -0,0 +1,688 @@
+use super::cards::{
+    Card, CardSide, CardsPage, CardsServerProps, ColumnRule, Copies, Direction, Kv, MAX_COPIES,
+    MergedCells, Rule, SheetArea, SheetSource, SourceKind, TextOptions, ValueFormat, Violation,
+    fill_template,
+};
+use cache::Part;
+use calamine::{Data, DeError, Dimensions, Range, Reader, Xlsx, open_workbook};
+use format::format_cell;
+use hidden::Hidden;
+use leptos::prelude::ServerFnError;
+use maps::{Maps, label_of, load_maps, with_map_rules};
+use std::{num::NonZeroUsize, path::Path, sync::Arc};
+use transform::transform;
+
+mod cache;
+mod delimited;
+mod duplicates;
+mod format;
+mod hidden;
+mod maps;
+mod plan;
+mod transform;
+mod validate;
+
+/// how many rows from the top are searched for the title row
+const GUESS_SCAN: usize = 30;
+/// how many rows after a candidate title row should agree on their types
+const GUESS_LOOKAHEAD: usize = 5;
+
+/// how many rows of the sheet get a preview in the title row picker
+pub const PREVIEW_ROWS: usize = 50;
+/// how many cells of a row are shown in its preview
+pub const PREVIEW_CELLS: usize = 4;
+/// how many characters of a cell are shown in a preview
+pub const PREVIEW_CHARS: usize = 16;
+
+#[derive(PartialEq, Clone, Copy)]
+enum CellKind {
+    Text,
+    Number,
+    Bool,
+    Date,
+    Error,
+}
+
+fn cell_kind(cell: &Data) -> Option<CellKind> {
+    match cell {
+        Data::Empty => None,
+        Data::String(s) if s.trim().is_empty() => None,
+        Data::String(_) => Some(CellKind::Text),
+        Data::Int(_) | Data::Float(_) => Some(CellKind::Number),
+        Data::Bool(_) => Some(CellKind::Bool),
+        Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => Some(CellKind::Date),
+        Data::Error(_) => Some(CellKind::Error),
+    }
+}
+
+/// a row looks like a title row when at least half of its cells are filled
+/// and every filled cell is text
+fn is_title_like(row: &[Data]) -> bool {
+    let kinds = row.iter().flat_map(cell_kind).collect::<Vec<_>>();
+    !kinds.is_empty() && kinds.len() * 2 >= row.len() && kinds.iter().all(|x| *x == CellKind::Text)
+}
+
+/// every column keeps the same kind of value along the given rows
+fn is_consistent(rows: &[&[Data]]) -> bool {
+    let width = rows.iter().map(|x| x.len()).max().unwrap_or_default();
+    !rows.is_empty()
+        && (0..width).all(|col| {
+            let mut kinds = rows.iter().flat_map(|row| row.get(col).and_then(cell_kind));
+            let first = kinds.next();
+            kinds.all(|x| Some(x) == first)
+        })
+}
+
+/// guess the title row as the first row that is mostly text and is followed
+/// by rows of consistent types
+pub fn guess_title_row(range: &Range<Data>) -> Option<NonZeroUsize> {
+    let rows = range
+        .rows()
+        .take(GUESS_SCAN + GUESS_LOOKAHEAD)
+        .collect::<Vec<_>>();
+    (0..rows.len().min(GUESS_SCAN))
+        .find(|&i| {
+            let after = &rows[i + 1..rows.len().min(i + 1 + GUESS_LOOKAHEAD)];
+            is_title_like(rows[i]) && is_consistent(after)
+        })
+        .and_then(|i| NonZeroUsize::new(i + 1))
+}
+
+/// zero based index of the first title row
+pub fn title_row_start(title_row_index: Option<NonZeroUsize>) -> usize {
+    title_row_index.map(|x| x.get() - 1).unwrap_or_default()
+}
+
+/// zero based absolute `(row, col)` of a cell reference like `$B$3`
+fn parse_cell(cell: &str) -> Option<(u32, u32)> {
+    let cell = cell.replace('$', "");
+    let split = cell.find(|x: char| x.is_ascii_digit())?;
+    let (letters, digits) = cell.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|x| x.is_ascii_alphabetic()) {
+        return None;
+    }
+    let col = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .try_fold(0u32, |acc, x| {
+            acc.checked_mul(26)?.checked_add((x - b'A' + 1) as u32)
+        })?;
+    let row = digits.parse::<u32>().ok()?;
+    Some((row.checked_sub(1)?, col - 1))
+}
+
+/// dimensions of an area reference like `A1:D20` or a single cell
+fn parse_area(area: &str) -> Option<Dimensions> {
+    match area.split_once(':') {
+        Some((start, end)) => Some(Dimensions::new(parse_cell(start)?, parse_cell(end)?)),
+        None => parse_cell(area).map(|x| Dimensions::new(x, x)),
+    }
+}
+
+/// sheet name and dimensions of a defined name formula like `'My Sheet'!$A$1:$D$20`
+fn parse_reference(formula: &str) -> Option<(String, Dimensions)> {
+    let (sheet, area) = formula.rsplit_once('!')?;
+    let sheet = sheet
+        .strip_prefix('\'')
+        .and_then(|x| x.strip_suffix('\''))
+        .map(|x| x.replace("''", "'"))
+        .unwrap_or_else(|| sheet.to_string());
+    Some((sheet, parse_area(area)?))
+}
+
+/// everything read from a sheet of a file, shared between calls by the cache
+struct Parsed {
+    range: Arc<Range<Data>>,
+    merged: Vec<Dimensions>,
+    /// `None` for files that can not hide rows
+    hidden: Option<Hidden>,
+    /// tables of the sheet with the dimensions of their data
+    tables: Vec<(String, Option<Dimensions>)>,
+    /// defined names of the workbook with their formulas
+    names: Vec<(String, String)>,
+}
+
+/// rough count of the bytes `range` takes in memory
+fn weight(range: &Range<Data>) -> usize {
+    let cells = range.width() * range.height() * std::mem::size_of::<Data>();
+    let strings = range
+        .used_cells()
+        .map(|(_, _, x)| match x {
+            Data::String(x) | Data::DateTimeIso(x) | Data::DurationIso(x) => x.len(),
+            _ => 0,
+        })
+        .sum::<usize>();
+    cells + strings
+}
+
+fn parse(path: &Path, sheet: &str, text: &TextOptions) -> Result<Parsed, ServerFnError> {
+    if SourceKind::of(path) == Some(SourceKind::Delimited) {
+        return Ok(Parsed {
+            range: Arc::new(delimited::read(path, text)?),
+            merged: Vec::new(),
+            hidden: None,
+            tables: Vec::new(),
+            names: Vec::new(),
+        });
+    }
+    let mut workbook: Xlsx<_> = open_workbook(path)?;
+    let range = workbook.worksheet_range(sheet)?;
+    let merged = workbook
+        .worksheet_merge_cells(sheet)
+        .transpose()?
+        .unwrap_or_default();
+    workbook.load_tables()?;
+    let mut tables = Vec::new();
+    for name in workbook
+        .table_names_in_sheet(sheet)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>()
+    {
+        let table = workbook.table_by_name(&name)?;
+        let dims = match (table.data().start(), table.data().end()) {
+            (Some(start), Some(end)) => Some(Dimensions::new(start, end)),
+            _ => None,
+        };
+        tables.push((name, dims));
+    }
+    Ok(Parsed {
+        range: Arc::new(range),
+        merged,
+        hidden: Some(Hidden::read(path, sheet)?),
+        tables,
+        names: workbook.defined_names().to_vec(),
+    })
+}
+
+fn parsed(path: &Path, sheet: &str, text: &TextOptions) -> Result<Arc<Parsed>, ServerFnError> {
+    cache::cached(path, Part::Sheet(sheet.to_string(), text.clone()), || {
+        let parsed = parse(path, sheet, text)?;
+        let weight = weight(&parsed.range);
+        Ok((parsed, weight))
+    })
+}
+
+/// tables and named ranges of the sheet that can be used instead of its used range
+pub fn sheet_areas(path: &Path, sheet: &str) -> Result<Vec<SheetArea>, ServerFnError> {
+    let parsed = parsed(path, sheet, &TextOptions::default())?;
+    let tables = parsed
+        .tables
+        .iter()
+        .map(|(name, _)| SheetArea::Table(name.clone()));
+    let names = parsed
+        .names
+        .iter()
+        .filter(|(name, formula)| {
+            !name.starts_with("_xlnm._")
+                && parse_reference(formula).is_some_and(|(x, _)| x == sheet)
+        })
+        .map(|(name, _)| SheetArea::Named(name.clone()));
+    Ok(tables.chain(names).collect())
+}
+
+/// names of the sheets of the workbook at `path`, a delimited text file has
+/// a single sheet named after the file
+pub fn sheets_names(path: &Path) -> Result<Vec<String>, ServerFnError> {
+    if SourceKind::of(path) == Some(SourceKind::Delimited) {
+        let stem = path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        return Ok(vec![stem]);
+    }
+    let names = cache::cached(path, Part::SheetsNames, || {
+        let workbook: Xlsx<_> = open_workbook(path)?;
+        let names = workbook.sheet_names();
+        let weight = names.iter().map(|x| x.len()).sum();
+        Ok((names, weight))
+    })?;
+    Ok(names.to_vec())
+}
+
+/// copy the value of every region of `merged` into all the cells it covers
+fn fill_merged(range: &mut Range<Data>, merged: &[Dimensions]) {
+    let (Some(start), Some(end)) = (range.start(), range.end()) else {
+        return;
+    };
+    for region in merged {
+        let Some(value) = range.get_value(region.start).cloned() else {
+            continue;
+        };
+        for row in region.start.0.max(start.0)..=region.end.0.min(end.0) {
+            for col in region.start.1.max(start.1)..=region.end.1.min(end.1) {
+                range.set_value((row, col), value.clone());
+            }
+        }
+    }
+}
+
+/// a worksheet range with its merged cells spread as configured
+pub struct Sheet {
+    pub range: Arc<Range<Data>>,
+    merged_cells: MergedCells,
+    skip_hidden: bool,
+    parsed: Arc<Parsed>,
+}
+
+impl Sheet {
+    pub fn open(source: &SheetSource) -> Result<Self, ServerFnError> {
+        let SheetSource {
+            path,
+            sheet,
+            text,
+            area,
+            merged_cells,
+            skip_hidden,
+        } = source;
+        let parsed = parsed(path, sheet, text)?;
+        let dims = match area {
+            SheetArea::Used => None,
+            SheetArea::Table(name) => match parsed.tables.iter().find(|(x, _)| x == name) {
+                // the data of a table starts right under its header
+                Some((_, Some(dims))) => {
+                    Some(((dims.start.0.saturating_sub(1), dims.start.1), dims.end))
+                }
+                Some((_, None)) => None,
+                None => return Err(ServerFnError::new(format!("table {name} is not found"))),
+            },
+            SheetArea::Named(name) => {
+                let Some((on, dims)) = parsed
+                    .names
+                    .iter()
+                    .find(|(x, _)| x == name)
+                    .and_then(|(_, formula)| parse_reference(formula))
+                else {
+                    return Err(ServerFnError::new(format!("name {name} is not a range")));
+                };
+                // the same addresses on another sheet are other cells
+                if on != *sheet {
+                    return Err(ServerFnError::new(format!(
+                        "name {name} is a range of the sheet {on}, not of {sheet}"
+                    )));
+                }
+                Some((dims.start, dims.end))
+            }
+        };
+        let fill = *merged_cells != MergedCells::Keep && !parsed.merged.is_empty();
+        let range = if dims.is_none() && !fill {
+            parsed.range.clone()
+        } else {
+            // a part of the sheet or one with its merged cells filled is a
+            // copy, kept in the cache on its own
+            let part = Part::Range(sheet.clone(), text.clone(), area.clone(), fill);
+            cache::cached(path, part, || {
+                let mut range = match dims {
+                    Some((start, end)) => parsed.range.range(start, end),
+                    None => Range::clone(&parsed.range),
+                };
+                if fill {
+                    fill_merged(&mut range, &parsed.merged);
+                }
+                let weight = weight(&range);
+                Ok((range, weight))
+            })?
+        };
+        Ok(Self {
+            range,
+            merged_cells: *merged_cells,
+            skip_hidden: *skip_hidden,
+            parsed,
+        })
+    }
+
+    fn merged(&self) -> &[Dimensions] {
+        match self.merged_cells {
+            MergedCells::Keep => &[],
+            MergedCells::Fill | MergedCells::Prefix => &self.parsed.merged,
+        }
+    }
+
+    /// only there when hidden rows and columns should be skipped
+    fn hidden(&self) -> Option<&Hidden> {
+        self.parsed.hidden.as_ref().filter(|_| self.skip_hidden)
+    }
+
+    fn start(&self) -> (u32, u32) {
+        self.range.start().unwrap_or_default()
+    }
+
+    /// the number from 1 of the spreadsheet row at `index` from `first_row`,
+    /// counting the rows above the range and the hidden ones
+    pub fn sheet_row(&self, first_row: usize, index: usize) -> usize {
+        self.start().0 as usize + first_row + index + 1
+    }
+
+    /// the visible rows starting from `first_row` with their index relative to it
+    pub fn rows(&self, first_row: usize) -> impl Iterator<Item = (usize, &[Data])> {
+        let (start_row, start_col) = self.start();
+        self.range
+            .rows()
+            .enumerate()
+            .skip(first_row)
+            .filter(move |(i, row)| {
+                let row_abs = start_row + *i as u32;
+                self.hidden().is_none_or(|x| {
+                    !x.is_row_hidden(row_abs) && !x.is_filtered(row_abs, row, start_col)
+                })
+            })
+            .map(move |(i, row)| (i - first_row, row))
+    }
+
+    pub fn is_column_visible(&self, col: usize) -> bool {
+        let start_col = self.start().1;
+        self.hidden()
+            .is_none_or(|x| !x.is_column_hidden(start_col + col as u32))
+    }
+
+    /// merged regions ending right above the title rows, used to prefix the
+    /// headers of the columns they cover
+    fn groups(&self, title_start: usize) -> Vec<String> {
+        let mut groups = vec![String::new(); self.range.width()];
+        let Some(start) = self.range.start() else {
+            return groups;
+        };
+        let title_start = start.0 as usize + title_start;
+        for region in self.merged().iter() {
+            if region.end.0 as usize + 1 != title_start || region.start.1 == region.end.1 {
+                continue;
+            }
+            let value = self
+                .range
+                .get_value(region.start)
+                .map(|x| x.to_string().trim().to_string())
+                .unwrap_or_default();
+            for col in region.start.1.max(start.1)..=region.end.1 {
+                if let Some(group) = groups.get_mut((col - start.1) as usize) {
+                    group.clone_from(&value);
+                }
+            }
+        }
+        groups
+    }
+
+    /// read the headers combining the cells of `rows_count` rows starting
+    /// from `title_row_index` as `upper / lower`
+    pub fn headers(
+        &self,
+        title_row_index: Option<NonZeroUsize>,
+        rows_count: NonZeroUsize,
+    ) -> Result<Vec<String>, DeError> {
+        let start = title_row_start(title_row_index);
+        let rows = self
+            .range
+            .rows()
+            .skip(start)
+            .take(rows_count.get())
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return Err(DeError::HeaderNotFound(format!(
+                "Error number {} should contain headers",
+                start + 1
+            )));
+        }
+        let groups = match self.merged_cells {
+            MergedCells::Prefix => self.groups(start),
+            MergedCells::Keep | MergedCells::Fill => vec![String::new(); self.range.width()],
+        };
+
+        let headers = groups
+            .into_iter()
+            .enumerate()
+            .map(|(col, group)| {
+                let mut parts = Vec::<String>::new();
+                if !group.is_empty() {
+                    parts.push(group);
+                }
+                for row in rows.iter() {
+                    let part = row[col].to_string().trim().to_string();
+                    if !part.is_empty() && parts.last() != Some(&part) {
+                        parts.push(part);
+                    }
+                }
+                parts.join(" / ")
+            })
+            .collect();
+
+        Ok(headers)
+    }
+}
+
+/// one card for every visible row under the title rows holding the chosen
+/// columns, skipping `offset` cards and keeping at most `limit` of them
+pub fn build_cards(
+    reqs: CardsServerProps,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<CardsPage, ServerFnError> {
+    if reqs.back.is_some() && reqs.layout.rows.is_none() {
+        return Err(ServerFnError::new(
+            "the rows of a page are needed to print the backs behind their fronts",
+        ));
+    }
+    let sheet = Sheet::open(&reqs.source)?;
+    let headers = sheet.headers(reqs.title_row_index, reqs.title_rows_count)?;
+    let first_row = title_row_start(reqs.title_row_index) + reqs.title_rows_count.get();
+    let plan = plan::plan(&reqs, &sheet, &headers, first_row)?;
+    let CardsServerProps {
+        columns_indexs,
+        back,
+        columns_directions,
+        columns_transforms,
+        format,
+        ..
+    } = reqs;
+
+    let visible = |indexs: Vec<usize>| {
+        indexs
+            .into_iter()
+            .filter(|x| *x < headers.len() && sheet.is_column_visible(*x))
+            .collect::<Vec<_>>()
+    };
+    let columns_indexs = visible(columns_indexs);
+    // the value of a cell as written on the cards
+    let value_of = |index: usize, cell: &Data| {
+        let transforms = columns_transforms
+            .iter()
+            .find(|x| x.index == index)
+            .map(|x| &x.transforms);
+        let format = ValueFormat {
+            thousands: format.thousands && !transforms.is_some_and(|x| x.ungrouped),
+            ..format
+        };
+        let value = match plan.maps.get(&index).and_then(|x| label_of(x, cell)) {
+            Some(label) => label.clone(),
+            None => format_cell(cell, &format),
+        };
+        match transforms {
+            Some(x) => transform(value, x),
+            None => value,
+        }
+    };
+    let back = back.map(|x| (x.text, visible(x.columns_indexs)));
+
+    let mut cards = Vec::new();
+    for planned in plan.from(offset) {
+        if limit.is_some_and(|x| cards.len() >= x) {
+            break;
+        }
+        let (i, first, count) = (planned.index, planned.first, planned.count);
+        let Some(row) = sheet.range.rows().nth(first_row + i) else {
+            continue;
+        };
+        let kvs = |indexs: &[usize]| {
+            let mut kvs = Vec::new();
+            for index in indexs.iter() {
+                let header = headers[*index].clone();
+                let value = value_of(*index, &row[*index]);
+                if !header.is_empty() && !value.is_empty() {
+                    let dir = columns_directions
+                        .iter()
+                        .find(|x| x.index == *index)
+                        .map(|x| x.dir)
+                        .or_else(|| Direction::detect(&value));
+                    kvs.push(Kv {
+                        key: header,
+                        value,
+                        dir,
+                    });
+                }
+            }
+            kvs
+        };
+        let (kv, back_kv) = (kvs(&columns_indexs), back.as_ref().map(|(_, x)| kvs(x)));
+        for copy in (offset.max(first) - first + 1)..=count {
+            if limit.is_some_and(|x| cards.len() >= x) {
+                break;
+            }
+            let mut card = Card {
+                row_index: i,
+                sheet_row: sheet.sheet_row(first_row, i),
+                kv: kv.clone(),
+                back: None,
+                copy,
+                copies: count,
+                duplicate: planned.duplicate,
+            };
+            card.back = back
+                .as_ref()
+                .zip(back_kv.clone())
+                .map(|((text, _), kv)| CardSide {
+                    text: fill_template(text, |name| {
+                        match headers.iter().position(|x| x == name) {
+                            Some(index) => Some(value_of(index, row.get(index)?)),
+                            None => card.variable(name),
+                        }
+                    }),
+                    kv,
+                });
+            cards.push(card);
+        }
+    }
+
+    Ok(CardsPage {
+        offset,
+        cards,
+        total: plan.total,
+        duplicates: plan.duplicates,
+    })
+}
+
+/// every cell of the card rows breaking one of the rules of `reqs` or
+/// missing from the map of its column
+pub fn validate_rows(reqs: &CardsServerProps) -> Result<Vec<Violation>, ServerFnError> {
+    let sheet = Sheet::open(&reqs.source)?;
+    let headers = sheet.headers(reqs.title_row_index, reqs.title_rows_count)?;
+    let first_row = title_row_start(reqs.title_row_index) + reqs.title_rows_count.get();
+    let maps = load_maps(&reqs.maps)?;
+    validate::violations(&sheet, first_row, &headers, &checks(reqs, &maps), &maps)
+}
+
+/// how many cards `row` makes
+fn copies_of(copies: &Copies, row: &[Data]) -> usize {
+    let count = match copies {
+        Copies::Fixed(x) => x.get(),
+        Copies::Column(index) => match row.get(*index) {
+            Some(Data::Int(x)) => (*x).max(0) as usize,
+            Some(Data::Float(x)) => x.round().max(0.0) as usize,
+            Some(x) => x.to_string().trim().parse::<usize>().unwrap_or(1),
+            None => 1,
+        },
+    };
+    count.min(MAX_COPIES)
+}
+
+/// the rules of `reqs` with the checks made besides them, on the columns
+/// with a map and on the column of copies
+fn checks(reqs: &CardsServerProps, maps: &Maps) -> Vec<ColumnRule> {
+    let mut rules = with_map_rules(reqs.rules.clone(), maps);
+    if let Copies::Column(index) = reqs.copies {
+        rules.push(ColumnRule {
+            index,
+            rule: Rule::Copies,
+        });
+    }
+    rules
+}
+
+/// the first `cells` non empty values of `row` each cut to `chars` characters
+pub fn row_preview(row: &[Data], cells: usize, chars: usize) -> Vec<String> {
+    row.iter()
+        .map(|x| x.to_string().trim().to_string())
+        .filter(|x| !x.is_empty())
+        .take(cells)
+        .map(|x| shorten(&x, chars))
+        .collect()
+}
+
+/// the first non empty value of every column starting from row `first_row`
+pub fn columns_samples(range: &Range<Data>, first_row: usize, chars: usize) -> Vec<String> {
+    (0..range.width())
+        .map(|col| {
+            range
+                .rows()
+                .skip(first_row)
+                .map(|row| row[col].to_string().trim().to_string())
+                .find(|x| !x.is_empty())
+                .map(|x| shorten(&x, chars))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn shorten(value: &str, chars: usize) -> String {
+    if value.chars().count() > chars {
+        let mut short = value.chars().take(chars).collect::<String>();
+        short.push('…');
+        short
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells() {
+        assert_eq!(parse_cell("A1"), Some((0, 0)));
+        assert_eq!(parse_cell("$B$3"), Some((2, 1)));
+        assert_eq!(parse_cell("aa10"), Some((9, 26)));
+        assert_eq!(parse_cell("XFD1048576"), Some((1048575, 16383)));
+        for cell in ["A0", "1A", "A", "A1B", "ZZZZZZZZ1", ""] {
+            assert_eq!(parse_cell(cell), None, "{cell}");
+        }
+    }
+
+    #[test]
+    fn copies() {
+        let column = Copies::Column(1);
+        let row = |x: Data| vec![Data::Empty, x];
+        assert_eq!(copies_of(&column, &row(Data::Int(3))), 3);
+        assert_eq!(copies_of(&column, &row(Data::Float(2.6))), 3);
+        assert_eq!(copies_of(&column, &row(Data::Int(-4))), 0);
+        assert_eq!(copies_of(&column, &row(Data::String(" 5 ".into()))), 5);
+        assert_eq!(copies_of(&column, &row(Data::String("many".into()))), 1);
+        assert_eq!(copies_of(&column, &[]), 1);
+        assert_eq!(
+            copies_of(&column, &row(Data::Int(1_000_000_000))),
+            MAX_COPIES
+        );
+        assert_eq!(copies_of(&column, &row(Data::Float(1e300))), MAX_COPIES);
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(
+            parse_reference("'My ''Sheet'''!$A$1:$D$20"),
+            Some(("My 'Sheet'".to_string(), Dimensions::new((0, 0), (19, 3))))
+        );
+        assert_eq!(
+            parse_reference("Data!C5"),
+            Some(("Data".to_string(), Dimensions::new((4, 2), (4, 2))))
+        );
+        assert_eq!(parse_reference("A1:B2"), None);
+        assert_eq!(parse_reference("Data!#REF!"), None);
+    }
+}
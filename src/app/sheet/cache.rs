@@ -0,0 +1,135 @@
+//! parsed files kept between server function calls. an entry is parsed again
+//! when its file changes on disk and the least recently used entries are
+//! dropped when the cache grows over its capacity.
+
+use crate::app::cards::{SheetArea, TextOptions};
+use leptos::prelude::ServerFnError;
+use std::{
+    any::Any,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
+    time::SystemTime,
+};
+
+/// what is cached of a file
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Part {
+    SheetsNames,
+    Sheet(String, TextOptions),
+    /// an area of a sheet, with its merged cells filled when `true`
+    Range(String, TextOptions, SheetArea, bool),
+    /// the rows making cards for the settings written as json
+    Plan(String),
+}
+
+/// a file changed on disk gets a new fingerprint
+#[derive(Clone, PartialEq, Eq)]
+struct Fingerprint {
+    modified: SystemTime,
+    size: u64,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Result<Self, ServerFnError> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            modified: metadata.modified()?,
+            size: metadata.len(),
+        })
+    }
+}
+
+struct Entry {
+    fingerprint: Fingerprint,
+    value: Arc<dyn Any + Send + Sync>,
+    weight: usize,
+    used: u64,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<(PathBuf, Part), Entry>,
+    weight: usize,
+    tick: u64,
+}
+
+impl Cache {
+    fn get(
+        &mut self,
+        key: &(PathBuf, Part),
+        fingerprint: &Fingerprint,
+    ) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        if entry.fingerprint != *fingerprint {
+            return None;
+        }
+        entry.used = self.tick;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: (PathBuf, Part), mut entry: Entry) {
+        self.tick += 1;
+        entry.used = self.tick;
+        if let Some(old) = self.entries.remove(&key) {
+            self.weight -= old.weight;
+        }
+        // how many bytes of parsed data are kept at most
+        let capacity = crate::config::config().cache_size;
+        if entry.weight > capacity {
+            return;
+        }
+        while self.weight + entry.weight > capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, x)| x.used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(old) = self.entries.remove(&oldest) {
+                self.weight -= old.weight;
+            }
+        }
+        self.weight += entry.weight;
+        self.entries.insert(key, entry);
+    }
+}
+
+static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Mutex::default);
+
+fn lock() -> std::sync::MutexGuard<'static, Cache> {
+    CACHE.lock().unwrap_or_else(|x| x.into_inner())
+}
+
+/// the cached `part` of the file at `path` or the one `load` returns with its
+/// weight in bytes when the file is new or changed
+pub fn cached<T: Send + Sync + 'static>(
+    path: &Path,
+    part: Part,
+    load: impl FnOnce() -> Result<(T, usize), ServerFnError>,
+) -> Result<Arc<T>, ServerFnError> {
+    let fingerprint = Fingerprint::of(path)?;
+    let key = (path.to_path_buf(), part);
+    if let Some(value) = lock().get(&key, &fingerprint)
+        && let Ok(value) = value.downcast::<T>()
+    {
+        return Ok(value);
+    }
+
+    // parsing happens outside the lock so other files are not kept waiting
+    let (value, weight) = load()?;
+    let value = Arc::new(value);
+    lock().insert(
+        key,
+        Entry {
+            fingerprint,
+            value: value.clone(),
+            weight,
+            used: 0,
+        },
+    );
+    Ok(value)
+}
@@ -0,0 +1,229 @@
+//! csv and tsv files read as a workbook of a single sheet
+
+use crate::app::cards::TextOptions;
+use calamine::{Data, Range};
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1256};
+use leptos::prelude::ServerFnError;
+use std::path::Path;
+
+/// delimiters tried when the file does not say which one it uses
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// how many lines are looked at to detect the delimiter
+const DETECT_LINES: usize = 20;
+/// how many bytes are looked at to detect utf-16 without a byte order mark
+const DETECT_BYTES: usize = 4096;
+
+/// utf-16 text of mostly latin or arabic letters has the high byte of every
+/// unit in a small set, this returns the encoding whose high bytes look so
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let bytes = &bytes[..bytes.len().min(DETECT_BYTES)];
+    if bytes.len() < 4 {
+        return None;
+    }
+    let high_ratio = |offset: usize| {
+        let highs = bytes.iter().skip(offset).step_by(2);
+        let count = highs.clone().count();
+        let matched = highs.filter(|x| matches!(x, 0x00 | 0x06)).count();
+        matched * 10 >= count * 9
+    };
+    if high_ratio(1) && !high_ratio(0) {
+        Some(UTF_16LE)
+    } else if high_ratio(0) && !high_ratio(1) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+fn decode(bytes: &[u8], label: &str) -> Result<String, ServerFnError> {
+    let encoding = if !label.is_empty() {
+        Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| ServerFnError::new(format!("unknown encoding {label}")))?
+    } else if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        encoding
+    } else if let Some(encoding) = detect_utf16(bytes) {
+        encoding
+    } else if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1256
+    };
+    let (text, _, _) = encoding.decode(bytes);
+    Ok(text.into_owned())
+}
+
+/// the delimiter found the same number of times on every one of the first lines
+fn detect_delimiter(text: &str) -> u8 {
+    let lines = text
+        .lines()
+        .filter(|x| !x.trim().is_empty())
+        .take(DETECT_LINES)
+        .collect::<Vec<_>>();
+    DELIMITERS
+        .into_iter()
+        .map(|delimiter| {
+            let counts = lines
+                .iter()
+                .map(|line| line.bytes().filter(|x| *x == delimiter).count())
+                .collect::<Vec<_>>();
+            let min = counts.iter().min().copied().unwrap_or_default();
+            let max = counts.iter().max().copied().unwrap_or_default();
+            (delimiter, min, max)
+        })
+        .filter(|(_, min, _)| *min > 0)
+        .max_by_key(|(_, min, max)| (min == max, *min))
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+/// whether `text` is an iso date like `2024-01-31`, with a time like
+/// `T10:30` or `T10:30:00` after it
+fn is_iso_date(text: &str) -> bool {
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    let fields = |text: &str, separator: char| {
+        text.split(separator)
+            .map(|x| {
+                let number = x
+                    .bytes()
+                    .all(|x| x.is_ascii_digit())
+                    .then(|| x.parse::<u32>().ok());
+                (x.len(), number.flatten())
+            })
+            .collect::<Vec<_>>()
+    };
+    matches!(
+        fields(date, '-')[..],
+        [(4, Some(_)), (2, Some(1..=12)), (2, Some(1..=31))]
+    ) && time.is_none_or(|time| {
+        matches!(
+            fields(time, ':')[..],
+            [(2, Some(0..=23)), (2, Some(0..=59))]
+                | [(2, Some(0..=23)), (2, Some(0..=59)), (2, Some(0..=59))]
+        )
+    })
+}
+
+/// the cell of a field, a number or a date when it is written exactly as the
+/// sheet would write the value back so that nothing shown changes unless a
+/// format of the card set asks for it
+fn typed(value: String) -> Data {
+    let text = value.trim();
+    if let Ok(x) = text.parse::<i64>()
+        && x.to_string() == text
+    {
+        return Data::Int(x);
+    }
+    if text.contains('.')
+        && let Ok(x) = text.parse::<f64>()
+        && x.is_finite()
+        && x.to_string() == text
+    {
+        return Data::Float(x);
+    }
+    if is_iso_date(text) {
+        return Data::DateTimeIso(text.to_string());
+    }
+    Data::String(value)
+}
+
+pub fn read(path: &Path, options: &TextOptions) -> Result<Range<Data>, ServerFnError> {
+    let bytes = std::fs::read(path)?;
+    let text = decode(&bytes, &options.encoding)?;
+    let delimiter = match options.delimiter {
+        Some(x) if x.is_ascii() => x as u8,
+        Some(x) => return Err(ServerFnError::new(format!("delimiter {x} is not ascii"))),
+        None if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("tsv")) =>
+        {
+            b'\t'
+        }
+        None => detect_delimiter(&text),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        rows.push(record?.iter().map(String::from).collect::<Vec<_>>());
+    }
+
+    let height = rows.len() as u32;
+    let width = rows.iter().map(|x| x.len()).max().unwrap_or_default() as u32;
+    if height == 0 || width == 0 {
+        return Ok(Range::empty());
+    }
+    let mut range = Range::new((0, 0), (height - 1, width - 1));
+    for (i, row) in rows.into_iter().enumerate() {
+        for (j, value) in row.into_iter().enumerate() {
+            if !value.trim().is_empty() {
+                range.set_value((i as u32, j as u32), typed(value));
+            }
+        }
+    }
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(detect_delimiter("a;b;c\n1;2;3\n"), b';');
+        assert_eq!(detect_delimiter("a\tb\n1\t2\n"), b'\t');
+        // the comma of a decimal number is found on some lines only
+        assert_eq!(detect_delimiter("name;price\npen;1,5\nbook;12\n"), b';');
+        assert_eq!(detect_delimiter("a|b\n\n1|2\n"), b'|');
+        assert_eq!(detect_delimiter("single column\n"), b',');
+    }
+
+    #[test]
+    fn utf16() {
+        let units = |text: &str| text.encode_utf16().collect::<Vec<_>>();
+        let le = units("الاسم,name\n")
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<_>>();
+        let be = units("الاسم,name\n")
+            .iter()
+            .flat_map(|x| x.to_be_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(detect_utf16(&le), Some(UTF_16LE));
+        assert_eq!(detect_utf16(&be), Some(UTF_16BE));
+        assert_eq!(detect_utf16("name,age\n".as_bytes()), None);
+        assert_eq!(detect_utf16("الاسم".as_bytes()), None);
+        assert_eq!(decode(&le, "").unwrap(), "الاسم,name\n");
+    }
+
+    #[test]
+    fn typed_fields() {
+        assert_eq!(typed("42".to_string()), Data::Int(42));
+        assert_eq!(typed("-7".to_string()), Data::Int(-7));
+        assert_eq!(typed("2.5".to_string()), Data::Float(2.5));
+        let iso = |x: &str| Data::DateTimeIso(x.to_string());
+        assert_eq!(typed("2024-01-31".to_string()), iso("2024-01-31"));
+        assert_eq!(
+            typed("2024-01-31T10:30".to_string()),
+            iso("2024-01-31T10:30")
+        );
+        // values written back differently stay text
+        for text in [
+            "007",
+            "+5",
+            "2.50",
+            "1e3",
+            "2024-13-01",
+            "2024-1-31",
+            "31/01/2024",
+        ] {
+            assert_eq!(typed(text.to_string()), Data::String(text.to_string()));
+        }
+    }
+}
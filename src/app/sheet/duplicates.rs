@@ -0,0 +1,132 @@
+//! rows holding the same values in the key columns of a card set
+
+use crate::app::cards::{Duplicates, KeepDuplicates, KeyMatching};
+use calamine::Data;
+use std::collections::{HashMap, HashSet};
+
+/// the rows sharing their key with another row and the ones of them left
+/// out by the kept duplicate
+#[derive(Default)]
+pub struct DuplicateRows {
+    pub marked: HashSet<usize>,
+    pub dropped: HashSet<usize>,
+}
+
+/// the duplicates among `rows`, rows with an empty key are never duplicates
+pub fn duplicate_rows<'a>(
+    rows: impl Iterator<Item = (usize, &'a [Data])>,
+    duplicates: &Duplicates,
+) -> DuplicateRows {
+    if duplicates.columns_indexs.is_empty() {
+        return DuplicateRows::default();
+    }
+    let mut groups = HashMap::<Vec<String>, Vec<usize>>::new();
+    for (i, row) in rows {
+        let key = duplicates
+            .columns_indexs
+            .iter()
+            .map(|x| {
+                let value = row.get(*x).map(|x| x.to_string()).unwrap_or_default();
+                match duplicates.matching {
+                    KeyMatching::Exact => value.trim().to_string(),
+                    KeyMatching::Normalized => normalize(&value),
+                }
+            })
+            .collect::<Vec<_>>();
+        if key.iter().any(|x| !x.is_empty()) {
+            groups.entry(key).or_default().push(i);
+        }
+    }
+
+    let mut found = DuplicateRows::default();
+    for rows in groups.into_values().filter(|x| x.len() > 1) {
+        let kept = match duplicates.keep {
+            KeepDuplicates::All => None,
+            KeepDuplicates::First => rows.first().copied(),
+            KeepDuplicates::Last => rows.last().copied(),
+        };
+        if kept.is_some() {
+            found
+                .dropped
+                .extend(rows.iter().filter(|x| Some(**x) != kept));
+        }
+        found.marked.extend(rows);
+    }
+    found
+}
+
+/// `value` case folded with its spaces collapsed and the arabic letters
+/// often written for one another unified
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|x| match x {
+            'أ' | 'إ' | 'آ' => 'ا',
+            'ة' => 'ه',
+            _ => x,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&str]) -> Vec<Vec<Data>> {
+        values
+            .iter()
+            .map(|x| vec![Data::String(x.to_string())])
+            .collect()
+    }
+
+    fn found(
+        values: &[&str],
+        matching: KeyMatching,
+        keep: KeepDuplicates,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let rows = rows(values);
+        let duplicates = Duplicates {
+            columns_indexs: vec![0],
+            matching,
+            keep,
+        };
+        let found = duplicate_rows(
+            rows.iter().enumerate().map(|(i, x)| (i, x.as_slice())),
+            &duplicates,
+        );
+        let sorted = |x: HashSet<usize>| {
+            let mut x = x.into_iter().collect::<Vec<_>>();
+            x.sort();
+            x
+        };
+        (sorted(found.marked), sorted(found.dropped))
+    }
+
+    #[test]
+    fn normalized_values() {
+        assert_eq!(normalize("  Ahmed   ALI "), "ahmed ali");
+        assert_eq!(normalize("أحمد إبراهيم آمنة"), "احمد ابراهيم امنه");
+    }
+
+    #[test]
+    fn kept_duplicates() {
+        let values = ["Ali", "ali", " Ali ", "", ""];
+        use KeepDuplicates::*;
+        assert_eq!(
+            found(&values, KeyMatching::Exact, All),
+            (vec![0, 2], vec![])
+        );
+        assert_eq!(
+            found(&values, KeyMatching::Normalized, First),
+            (vec![0, 1, 2], vec![1, 2])
+        );
+        assert_eq!(
+            found(&values, KeyMatching::Normalized, Last),
+            (vec![0, 1, 2], vec![0, 1])
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! numbers and dates of the cells written with the digits, separators,
+//! month names and calendar chosen for a card set
+
+use crate::app::cards::{Calendar, DateLocale, Digits, ValueFormat};
+use calamine::Data;
+
+const ARABIC_MONTHS: [&str; 12] = [
+    "يناير",
+    "فبراير",
+    "مارس",
+    "أبريل",
+    "مايو",
+    "يونيو",
+    "يوليو",
+    "أغسطس",
+    "سبتمبر",
+    "أكتوبر",
+    "نوفمبر",
+    "ديسمبر",
+];
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const ARABIC_HIJRI_MONTHS: [&str; 12] = [
+    "محرم",
+    "صفر",
+    "ربيع الأول",
+    "ربيع الآخر",
+    "جمادى الأولى",
+    "جمادى الآخرة",
+    "رجب",
+    "شعبان",
+    "رمضان",
+    "شوال",
+    "ذو القعدة",
+    "ذو الحجة",
+];
+const ENGLISH_HIJRI_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi al-Awwal",
+    "Rabi al-Thani",
+    "Jumada al-Awwal",
+    "Jumada al-Thani",
+    "Rajab",
+    "Shaban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qadah",
+    "Dhu al-Hijjah",
+];
+
+/// the text of `cell` on a card, a date asked for in the hijri calendar is
+/// written in numbers when its locale keeps it as stored
+pub fn format_cell(cell: &Data, format: &ValueFormat) -> String {
+    let format = &match format.date {
+        DateLocale::AsStored if format.calendar == Calendar::Hijri => ValueFormat {
+            date: DateLocale::Numeric,
+            ..*format
+        },
+        _ => *format,
+    };
+    match cell {
+        Data::Int(x) => format_number(&x.to_string(), format),
+        Data::Float(x) => format_number(&x.to_string(), format),
+        Data::DateTime(x) if x.is_datetime() && format.date != DateLocale::AsStored => {
+            let (year, month, day, hour, minute, _, _) = x.to_ymd_hms_milli();
+            let time = (hour, minute) != (0, 0);
+            let date = Date {
+                year: year.into(),
+                month: month.into(),
+                day: day.into(),
+            };
+            format_date(date, time.then_some((hour, minute)), format)
+        }
+        Data::DateTimeIso(x) if format.date != DateLocale::AsStored => match parse_iso(x) {
+            Some((date, time)) => format_date(date, time, format),
+            None => x.clone(),
+        },
+        _ => cell.to_string(),
+    }
+}
+
+/// `number` as rust writes it with the separators and digits of `format`
+fn format_number(number: &str, format: &ValueFormat) -> String {
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(x) => ("-", x),
+        None => ("", number),
+    };
+    let (whole, fraction) = match number.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (number, None),
+    };
+    let (group, point) = match format.digits {
+        Digits::Western => (',', '.'),
+        Digits::ArabicIndic => ('\u{066C}', '\u{066B}'),
+    };
+    let mut text = sign.to_string();
+    for (i, x) in whole.chars().enumerate() {
+        if format.thousands && i > 0 && (whole.len() - i) % 3 == 0 {
+            text.push(group);
+        }
+        text.push(x);
+    }
+    if let Some(fraction) = fraction {
+        text.push(point);
+        text.push_str(fraction);
+    }
+    digits(&text, format.digits)
+}
+
+/// `text` with its western digits written in `digits`
+fn digits(text: &str, digits: Digits) -> String {
+    match digits {
+        Digits::Western => text.to_string(),
+        Digits::ArabicIndic => text
+            .chars()
+            .map(|x| match x.to_digit(10) {
+                Some(d) if x.is_ascii_digit() => char::from_u32(0x0660 + d).unwrap_or(x),
+                _ => x,
+            })
+            .collect(),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Date {
+    year: i64,
+    month: i64,
+    day: i64,
+}
+
+/// the date and the time of `2024-01-31` or `2024-01-31T10:30:00`
+fn parse_iso(text: &str) -> Option<(Date, Option<(u8, u8)>)> {
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    let mut parts = date.splitn(3, '-').map(|x| x.parse::<i64>().ok());
+    let date = Date {
+        year: parts.next()??,
+        month: parts.next()??,
+        day: parts.next()??,
+    };
+    let time = time.and_then(|time| {
+        let mut parts = time.splitn(3, ':').map(|x| x.parse::<u8>().ok());
+        Some((parts.next()??, parts.next()??))
+    });
+    Some((date, time.filter(|x| *x != (0, 0))))
+}
+
+fn format_date(date: Date, time: Option<(u8, u8)>, format: &ValueFormat) -> String {
+    let (date, hijri) = match format.calendar {
+        Calendar::Gregorian => (date, false),
+        Calendar::Hijri => (to_hijri(date), true),
+    };
+    let Date { year, month, day } = date;
+    let months = match (format.date, hijri) {
+        (DateLocale::Arabic, false) => Some(ARABIC_MONTHS),
+        (DateLocale::Arabic, true) => Some(ARABIC_HIJRI_MONTHS),
+        (DateLocale::English, false) => Some(ENGLISH_MONTHS),
+        (DateLocale::English, true) => Some(ENGLISH_HIJRI_MONTHS),
+        _ => None,
+    };
+    let month_name = usize::try_from(month - 1)
+        .ok()
+        .and_then(|i| months?.get(i).copied());
+    let mut text = match month_name {
+        Some(name) => format!("{day} {name} {year}"),
+        None => format!("{year}/{month:02}/{day:02}"),
+    };
+    if hijri {
+        text.push_str(match format.date {
+            DateLocale::English => " AH",
+            _ => " هـ",
+        });
+    }
+    if let Some((hour, minute)) = time {
+        text.push_str(&format!(" {hour:02}:{minute:02}"));
+    }
+    digits(&text, format.digits)
+}
+
+/// the day of the tabular islamic calendar falling on a gregorian `date`
+fn to_hijri(Date { year, month, day }: Date) -> Date {
+    let a = (14 - month) / 12;
+    let y = year + 4800 - a;
+    let m = month + 12 * a - 3;
+    let julian_day = day + (153 * m + 2) / 5 + 365 * y + y / 4 - y / 100 + y / 400 - 32045;
+
+    let l = julian_day - 1948440 + 10632;
+    let n = (l - 1) / 10631;
+    let l = l - 10631 * n + 354;
+    let j = ((10985 - l) / 5316) * ((50 * l) / 17719) + (l / 5670) * ((43 * l) / 15238);
+    let l = l - ((30 - j) / 15) * ((17719 * j) / 50) - (j / 16) * ((15238 * j) / 43) + 29;
+    let month = (24 * l) / 709;
+    Date {
+        year: 30 * n + j - 30,
+        month,
+        day: l - (709 * month) / 24,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hijri(year: i64, month: i64, day: i64) -> (i64, i64, i64) {
+        let Date { year, month, day } = to_hijri(Date { year, month, day });
+        (year, month, day)
+    }
+
+    #[test]
+    fn hijri_dates() {
+        assert_eq!(hijri(622, 7, 19), (1, 1, 1));
+        assert_eq!(hijri(2000, 1, 1), (1420, 9, 24));
+        // the tabular 1445 is a leap year, its last month has 30 days
+        assert_eq!(hijri(2024, 7, 7), (1445, 12, 30));
+        assert_eq!(hijri(2024, 7, 8), (1446, 1, 1));
+    }
+
+    #[test]
+    fn numbers() {
+        let grouped = ValueFormat {
+            thousands: true,
+            ..ValueFormat::default()
+        };
+        assert_eq!(format_number("1234567.25", &grouped), "1,234,567.25");
+        assert_eq!(format_number("-1234", &grouped), "-1,234");
+        assert_eq!(format_number("-123", &grouped), "-123");
+        assert_eq!(format_number("1234567", &ValueFormat::default()), "1234567");
+        let arabic = ValueFormat {
+            digits: Digits::ArabicIndic,
+            ..grouped
+        };
+        assert_eq!(format_number("-1234.5", &arabic), "-١٬٢٣٤٫٥");
+    }
+
+    #[test]
+    fn dates() {
+        let format = ValueFormat {
+            date: DateLocale::English,
+            ..ValueFormat::default()
+        };
+        let cell = Data::DateTimeIso("2024-01-31T10:30:00".to_string());
+        assert_eq!(format_cell(&cell, &format), "31 January 2024 10:30");
+        let cell = Data::DateTimeIso("2024-01-31".to_string());
+        assert_eq!(format_cell(&cell, &ValueFormat::default()), "2024-01-31");
+        let hijri = ValueFormat {
+            calendar: Calendar::Hijri,
+            ..ValueFormat::default()
+        };
+        assert_eq!(format_cell(&cell, &hijri), "1445/07/20 هـ");
+    }
+}
@@ -0,0 +1,241 @@
+//! hidden rows, hidden columns and the saved autofilter of a worksheet.
+//! calamine does not expose them so they are read from the xlsx package.
+
+use super::parse_area;
+use calamine::{Data, Dimensions};
+use leptos::prelude::ServerFnError;
+use quick_xml::{
+    Reader,
+    escape::unescape,
+    events::{BytesStart, Event},
+};
+use std::{collections::HashSet, fs::File, io::BufReader, path::Path};
+use zip::ZipArchive;
+
+struct FilterColumn {
+    /// absolute column index
+    col: u32,
+    blank: bool,
+    values: Vec<String>,
+}
+
+struct AutoFilter {
+    dims: Dimensions,
+    columns: Vec<FilterColumn>,
+}
+
+#[derive(Default)]
+pub struct Hidden {
+    /// absolute indexes of hidden rows
+    rows: HashSet<u32>,
+    /// absolute inclusive spans of hidden columns
+    cols: Vec<(u32, u32)>,
+    filter: Option<AutoFilter>,
+}
+
+fn attr(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|x| x.key.local_name().as_ref() == key)
+        .and_then(|x| {
+            unescape(&String::from_utf8_lossy(&x.value))
+                .ok()
+                .map(|x| x.into_owned())
+        })
+}
+
+fn is_true(e: &BytesStart, key: &[u8]) -> bool {
+    attr(e, key).is_some_and(|x| x == "1" || x == "true")
+}
+
+/// call `f` with every opening tag of the part `name` of the package
+fn for_each_tag(
+    archive: &mut ZipArchive<File>,
+    name: &str,
+    mut f: impl FnMut(&BytesStart),
+) -> Result<(), ServerFnError> {
+    let part = archive.by_name(name)?;
+    let mut reader = Reader::from_reader(BufReader::new(part));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) => f(&e),
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// path of the worksheet part named `sheet` inside the package
+fn sheet_part(archive: &mut ZipArchive<File>, sheet: &str) -> Result<String, ServerFnError> {
+    let mut id = None;
+    for_each_tag(archive, "xl/workbook.xml", |e| {
+        if e.local_name().as_ref() == b"sheet" && attr(e, b"name").as_deref() == Some(sheet) {
+            id = attr(e, b"id");
+        }
+    })?;
+    let Some(id) = id else {
+        return Err(ServerFnError::new(format!("sheet {sheet} is not found")));
+    };
+
+    let mut target = None;
+    for_each_tag(archive, "xl/_rels/workbook.xml.rels", |e| {
+        if e.local_name().as_ref() == b"Relationship" && attr(e, b"Id") == Some(id.clone()) {
+            target = attr(e, b"Target");
+        }
+    })?;
+    match target {
+        Some(target) => Ok(match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{target}"),
+        }),
+        None => Err(ServerFnError::new(format!("sheet {sheet} has no part"))),
+    }
+}
+
+impl Hidden {
+    pub fn read(path: &Path, sheet: &str) -> Result<Self, ServerFnError> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let part = sheet_part(&mut archive, sheet)?;
+
+        let mut hidden = Self::default();
+        for_each_tag(&mut archive, &part, |e| match e.local_name().as_ref() {
+            b"row" if is_true(e, b"hidden") => {
+                if let Some(row) = attr(e, b"r").and_then(|x| x.parse::<u32>().ok()) {
+                    hidden.rows.insert(row.saturating_sub(1));
+                }
+            }
+            b"col" if is_true(e, b"hidden") => {
+                let min = attr(e, b"min").and_then(|x| x.parse::<u32>().ok());
+                let max = attr(e, b"max").and_then(|x| x.parse::<u32>().ok());
+                if let (Some(min), Some(max)) = (min, max) {
+                    hidden
+                        .cols
+                        .push((min.saturating_sub(1), max.saturating_sub(1)));
+                }
+            }
+            b"autoFilter" => {
+                hidden.filter =
+                    attr(e, b"ref")
+                        .and_then(|x| parse_area(&x))
+                        .map(|dims| AutoFilter {
+                            dims,
+                            columns: Vec::new(),
+                        });
+            }
+            b"filterColumn" => {
+                if let Some(filter) = hidden.filter.as_mut()
+                    && let Some(col) = attr(e, b"colId").and_then(|x| x.parse::<u32>().ok())
+                {
+                    filter.columns.push(FilterColumn {
+                        col: filter.dims.start.1 + col,
+                        blank: false,
+                        values: Vec::new(),
+                    });
+                }
+            }
+            b"filters" => {
+                if let Some(column) = hidden.filter.as_mut().and_then(|x| x.columns.last_mut()) {
+                    column.blank = is_true(e, b"blank");
+                }
+            }
+            b"filter" => {
+                if let Some(column) = hidden.filter.as_mut().and_then(|x| x.columns.last_mut())
+                    && let Some(value) = attr(e, b"val")
+                {
+                    column.values.push(value.trim().to_lowercase());
+                }
+            }
+            _ => (),
+        })?;
+        Ok(hidden)
+    }
+
+    pub fn is_row_hidden(&self, row: u32) -> bool {
+        self.rows.contains(&row)
+    }
+
+    pub fn is_column_hidden(&self, col: u32) -> bool {
+        self.cols
+            .iter()
+            .any(|(min, max)| (*min..=*max).contains(&col))
+    }
+
+    /// the row at absolute index `row` starting at absolute column
+    /// `start_col` is excluded by the value lists of the saved autofilter
+    pub fn is_filtered(&self, row: u32, cells: &[Data], start_col: u32) -> bool {
+        let Some(filter) = self.filter.as_ref() else {
+            return false;
+        };
+        if row <= filter.dims.start.0 || row > filter.dims.end.0 {
+            return false;
+        }
+        filter.columns.iter().any(|column| {
+            if column.values.is_empty() && !column.blank {
+                return false;
+            }
+            let cell = column
+                .col
+                .checked_sub(start_col)
+                .and_then(|x| cells.get(x as usize));
+            let value = cell
+                .map(|x| x.to_string().trim().to_lowercase())
+                .unwrap_or_default();
+            if value.is_empty() {
+                return !column.blank;
+            }
+            let number = value.parse::<f64>().ok();
+            !column.values.iter().any(|x| {
+                *x == value || number.is_some_and(|n| x.parse::<f64>().is_ok_and(|x| x == n))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a filter over `B2:C10` keeping `yes` or `1` in column B and blanks
+    /// or `x` in column C
+    fn hidden() -> Hidden {
+        Hidden {
+            filter: Some(AutoFilter {
+                dims: Dimensions::new((1, 1), (9, 2)),
+                columns: vec![
+                    FilterColumn {
+                        col: 1,
+                        blank: false,
+                        values: vec!["yes".to_string(), "1".to_string()],
+                    },
+                    FilterColumn {
+                        col: 2,
+                        blank: true,
+                        values: vec!["x".to_string()],
+                    },
+                ],
+            }),
+            ..Hidden::default()
+        }
+    }
+
+    #[test]
+    fn filtered_rows() {
+        let hidden = hidden();
+        let row = |b: Data, c: Data| vec![Data::Empty, b, c];
+        let text = |x: &str| Data::String(x.to_string());
+        // the header row of the filter and the rows outside it are kept
+        assert!(!hidden.is_filtered(1, &row(text("no"), Data::Empty), 0));
+        assert!(!hidden.is_filtered(10, &row(text("no"), Data::Empty), 0));
+
+        assert!(!hidden.is_filtered(2, &row(text(" Yes "), Data::Empty), 0));
+        assert!(!hidden.is_filtered(2, &row(Data::Float(1.0), text("X")), 0));
+        assert!(hidden.is_filtered(2, &row(text("no"), Data::Empty), 0));
+        assert!(hidden.is_filtered(2, &row(Data::Empty, Data::Empty), 0));
+        assert!(hidden.is_filtered(2, &row(text("yes"), text("y")), 0));
+        // the cells of a range starting at column B
+        assert!(!hidden.is_filtered(2, &[text("yes"), Data::Empty], 1));
+    }
+}
@@ -0,0 +1,54 @@
+//! the labels of coded values, entered with the card set or read from
+//! another sheet
+
+use super::Sheet;
+use crate::app::cards::{ColumnMap, ColumnRule, MapEntry, Rule, ValueMap};
+use calamine::Data;
+use leptos::prelude::ServerFnError;
+use std::collections::{BTreeMap, HashMap};
+
+/// the label of every value by the index of its column
+pub type Maps = BTreeMap<usize, HashMap<String, String>>;
+
+/// the maps of the columns with their sheets read under their title row,
+/// the first label of a value written twice is kept
+pub fn load_maps(maps: &[ColumnMap]) -> Result<Maps, ServerFnError> {
+    let mut loaded = Maps::new();
+    for ColumnMap { index, map } in maps {
+        let labels = loaded.entry(*index).or_default();
+        let mut add = |value: String, label: String| {
+            if !value.is_empty() {
+                labels.entry(value).or_insert(label);
+            }
+        };
+        match map {
+            ValueMap::Inline(entries) => {
+                for MapEntry { value, label } in entries {
+                    add(value.trim().to_string(), label.trim().to_string());
+                }
+            }
+            ValueMap::Sheet(source) => {
+                let sheet = Sheet::open(source)?;
+                for (_, row) in sheet.rows(1) {
+                    let cell = |i: usize| row.get(i).map(|x| x.to_string().trim().to_string());
+                    add(cell(0).unwrap_or_default(), cell(1).unwrap_or_default());
+                }
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+/// the label of `cell` in `labels`
+pub fn label_of<'a>(labels: &'a HashMap<String, String>, cell: &Data) -> Option<&'a String> {
+    labels.get(cell.to_string().trim())
+}
+
+/// `rules` with a check of the values of every column with a map
+pub fn with_map_rules(mut rules: Vec<ColumnRule>, maps: &Maps) -> Vec<ColumnRule> {
+    rules.extend(maps.keys().map(|index| ColumnRule {
+        index: *index,
+        rule: Rule::Mapped,
+    }));
+    rules
+}
@@ -0,0 +1,120 @@
+//! the rows of a card set making cards, worked out once for its settings and
+//! shared by every page of cards fetched after
+
+use super::{
+    Sheet,
+    cache::{self, Part},
+    checks, copies_of,
+    duplicates::duplicate_rows,
+    maps::{Maps, load_maps},
+    validate,
+};
+use crate::app::cards::{CardsServerProps, ValueMap};
+use leptos::prelude::ServerFnError;
+use std::{collections::HashSet, sync::Arc, time::SystemTime};
+
+/// a row making cards, numbered from the first row under the title rows
+pub struct PlannedRow {
+    pub index: usize,
+    /// the number of its first card in the whole set
+    pub first: usize,
+    /// how many cards it makes
+    pub count: usize,
+    /// another row has the same key
+    pub duplicate: bool,
+}
+
+pub struct Plan {
+    pub rows: Vec<PlannedRow>,
+    /// how many cards the whole set makes
+    pub total: usize,
+    /// how many rows share their key with another row
+    pub duplicates: usize,
+    pub maps: Maps,
+}
+
+impl Plan {
+    /// the rows from the one making the card numbered `offset`
+    pub fn from(&self, offset: usize) -> &[PlannedRow] {
+        let start = self.rows.partition_point(|x| x.first + x.count <= offset);
+        &self.rows[start..]
+    }
+}
+
+/// the plan of `reqs` over `sheet`, made again when the sheet, the file of
+/// one of its maps or a setting it depends on changes
+pub fn plan(
+    reqs: &CardsServerProps,
+    sheet: &Sheet,
+    headers: &[String],
+    first_row: usize,
+) -> Result<Arc<Plan>, ServerFnError> {
+    let maps_modified = reqs
+        .maps
+        .iter()
+        .filter_map(|x| match &x.map {
+            ValueMap::Inline(_) => None,
+            ValueMap::Sheet(source) => Some(source),
+        })
+        .map(|x| std::fs::metadata(&x.path).and_then(|x| x.modified()).ok())
+        .collect::<Vec<Option<SystemTime>>>();
+    let key = serde_json::to_string(&(
+        &reqs.source,
+        reqs.title_row_index,
+        reqs.title_rows_count,
+        &reqs.copies,
+        &reqs.rules,
+        reqs.exclude_invalid,
+        &reqs.duplicates,
+        &reqs.maps,
+        maps_modified,
+    ))?;
+    cache::cached(&reqs.source.path, Part::Plan(key), || {
+        let plan = make(reqs, sheet, headers, first_row)?;
+        let weight = plan.rows.len() * std::mem::size_of::<PlannedRow>();
+        Ok((plan, weight))
+    })
+}
+
+fn make(
+    reqs: &CardsServerProps,
+    sheet: &Sheet,
+    headers: &[String],
+    first_row: usize,
+) -> Result<Plan, ServerFnError> {
+    let maps = load_maps(&reqs.maps)?;
+    let invalid = if reqs.exclude_invalid {
+        validate::violations(sheet, first_row, headers, &checks(reqs, &maps), &maps)?
+            .into_iter()
+            .map(|x| x.row_index)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let found = duplicate_rows(
+        sheet.rows(first_row).filter(|(i, _)| !invalid.contains(i)),
+        &reqs.duplicates,
+    );
+
+    let mut rows = Vec::new();
+    let mut total = 0;
+    for (index, row) in sheet.rows(first_row) {
+        if invalid.contains(&index) || found.dropped.contains(&index) {
+            continue;
+        }
+        let count = copies_of(&reqs.copies, row);
+        rows.push(PlannedRow {
+            index,
+            first: total,
+            count,
+            duplicate: found.marked.contains(&index),
+        });
+        total += count;
+    }
+    Ok(Plan {
+        rows,
+        total,
+        duplicates: found.marked.len(),
+        maps,
+    })
+}
@@ -0,0 +1,115 @@
+//! the clean up of values typed inconsistently, chosen for every column
+
+use crate::app::cards::Transforms;
+
+/// the arabic diacritics, the superscript alef and the quranic marks
+fn is_tashkeel(x: char) -> bool {
+    matches!(x, '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{06D6}'..='\u{06ED}')
+}
+
+fn is_latin(x: char) -> bool {
+    x.is_ascii_alphabetic() || (matches!(x, '\u{00C0}'..='\u{024F}') && x.is_alphabetic())
+}
+
+/// `value` with `transforms` applied
+pub fn transform(value: String, transforms: &Transforms) -> String {
+    if transforms.is_empty() {
+        return value;
+    }
+    let mut chars = value
+        .chars()
+        .filter(|x| !(transforms.strip_tashkeel && is_tashkeel(*x)))
+        .filter(|x| !(transforms.remove_tatweel && *x == 'ـ'))
+        .map(|x| match x {
+            'أ' | 'إ' | 'آ' | 'ٱ' if transforms.unify_letters => 'ا',
+            'ى' if transforms.unify_letters => 'ي',
+            _ => x,
+        })
+        .collect::<Vec<_>>();
+    if transforms.collapse_spaces {
+        chars.dedup_by(|x, before| x.is_whitespace() && before.is_whitespace());
+        for x in chars.iter_mut().filter(|x| x.is_whitespace()) {
+            *x = ' ';
+        }
+    }
+    let mut value = chars.into_iter().collect::<String>();
+    if transforms.trim {
+        value = value.trim().to_string();
+    }
+    if transforms.title_case {
+        value = title_case(&value);
+    }
+    match transforms.max_length {
+        Some(max) if value.chars().count() > max.get() => {
+            let mut cut = value
+                .chars()
+                .take(max.get() - 1)
+                .collect::<String>()
+                .trim_end()
+                .to_string();
+            cut.push('…');
+            cut
+        }
+        _ => value,
+    }
+}
+
+/// every latin word of `value` capitalized, other scripts left as they are
+fn title_case(value: &str) -> String {
+    let mut titled = String::with_capacity(value.len());
+    let mut in_word = false;
+    for x in value.chars() {
+        if is_latin(x) {
+            if in_word {
+                titled.extend(x.to_lowercase());
+            } else {
+                titled.extend(x.to_uppercase());
+            }
+        } else {
+            titled.push(x);
+        }
+        in_word = x.is_alphanumeric() || x == '\'';
+    }
+    titled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn arabic_clean_up() {
+        let transforms = Transforms {
+            strip_tashkeel: true,
+            remove_tatweel: true,
+            unify_letters: true,
+            collapse_spaces: true,
+            trim: true,
+            ..Transforms::default()
+        };
+        let value = "  أَحْمَد \t ـعلىـ  ".to_string();
+        assert_eq!(transform(value.clone(), &transforms), "احمد علي");
+        assert_eq!(transform(value.clone(), &Transforms::default()), value);
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(title_case("JOHN o'neil-SMITH"), "John O'neil-Smith");
+        assert_eq!(title_case("محمد ali ÉLODIE"), "محمد Ali Élodie");
+        assert_eq!(title_case("2nd floor"), "2nd Floor");
+    }
+
+    #[test]
+    fn max_length() {
+        let max = |x: usize| Transforms {
+            max_length: NonZeroUsize::new(x),
+            ..Transforms::default()
+        };
+        assert_eq!(transform("abcdef".to_string(), &max(6)), "abcdef");
+        assert_eq!(transform("abcdef".to_string(), &max(4)), "abc…");
+        // the cut is counted in characters and drops the space before the ellipsis
+        assert_eq!(transform("محمد علي".to_string(), &max(6)), "محمد…");
+        assert_eq!(transform("abc".to_string(), &max(1)), "…");
+    }
+}
@@ -0,0 +1,91 @@
+//! the rules a card set puts on the values of its columns, checked on every
+//! visible row under the title rows
+
+use super::{
+    Sheet,
+    maps::{Maps, label_of},
+};
+use crate::app::cards::{ColumnRule, MAX_COPIES, Rule, Violation};
+use calamine::Data;
+use leptos::prelude::ServerFnError;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// every cell of the rows from `first_row` breaking one of `rules`, by row,
+/// `maps` holding the labels `Rule::Mapped` looks the values up in
+pub fn violations(
+    sheet: &Sheet,
+    first_row: usize,
+    headers: &[String],
+    rules: &[ColumnRule],
+    maps: &Maps,
+) -> Result<Vec<Violation>, ServerFnError> {
+    let regexes = rules
+        .iter()
+        .map(|x| match &x.rule {
+            Rule::Regex(pattern) => {
+                Regex::new(&format!("^(?:{pattern})$"))
+                    .map(Some)
+                    .map_err(|e| {
+                        ServerFnError::new(format!("the pattern {pattern} is not valid: {e}"))
+                    })
+            }
+            _ => Ok(None),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let rows = sheet.rows(first_row).collect::<Vec<_>>();
+
+    let mut violations = Vec::new();
+    for (ColumnRule { index, rule }, regex) in rules.iter().zip(&regexes) {
+        if *index >= headers.len() || !sheet.is_column_visible(*index) {
+            continue;
+        }
+        let cells = rows
+            .iter()
+            .map(|(i, row)| (*i, row.get(*index).unwrap_or(&Data::Empty)))
+            .map(|(i, cell)| (i, cell, cell.to_string().trim().to_string()))
+            .collect::<Vec<_>>();
+        let mut counts = HashMap::<&str, usize>::new();
+        if *rule == Rule::Unique {
+            for (_, _, text) in &cells {
+                *counts.entry(text).or_default() += 1;
+            }
+        }
+        for (row_index, cell, text) in &cells {
+            let valid = match rule {
+                Rule::Required => !text.is_empty(),
+                _ if text.is_empty() => true,
+                Rule::Regex(_) => regex.as_ref().is_some_and(|x| x.is_match(text)),
+                Rule::Length { min, max } => {
+                    let length = text.chars().count();
+                    min.is_none_or(|x| length >= x) && max.is_none_or(|x| length <= x)
+                }
+                Rule::Range { min, max } => number(cell, text)
+                    .is_some_and(|n| min.is_none_or(|x| n >= x) && max.is_none_or(|x| n <= x)),
+                Rule::Unique => counts.get(text.as_str()) == Some(&1),
+                Rule::Allowed(values) => values.iter().any(|x| x.trim() == text),
+                Rule::Copies => number(cell, text).is_none_or(|x| x <= MAX_COPIES as f64),
+                Rule::Mapped => maps.get(index).is_some_and(|x| label_of(x, cell).is_some()),
+            };
+            if !valid {
+                violations.push(Violation {
+                    row_index: *row_index,
+                    sheet_row: sheet.sheet_row(first_row, *row_index),
+                    column: headers[*index].clone(),
+                    value: text.clone(),
+                    rule: rule.clone(),
+                });
+            }
+        }
+    }
+    violations.sort_by_key(|x| x.row_index);
+    Ok(violations)
+}
+
+fn number(cell: &Data, text: &str) -> Option<f64> {
+    match cell {
+        Data::Int(x) => Some(*x as f64),
+        Data::Float(x) => Some(*x),
+        _ => text.parse().ok(),
+    }
+}
@@ -1,4 +1,10 @@
-use crate::app::cards::CardsServerProps;
+use crate::app::cards::{
+    Calendar, CardBack, CardsServerProps, ColumnDirection, ColumnMap, ColumnRule, ColumnTransforms,
+    Copies, DateLocale, Digits, Direction, Duplicates, KeepDuplicates, KeyMatching, MAX_COPIES,
+    MAX_PAGE_COLUMNS, MAX_PAGE_ROWS, MapEntry, MergedCells, PageLayout, Rule, SheetArea,
+    SheetSource, SourceKind, TextOptions, Transforms, ValueFormat, ValueMap,
+};
+use crate::app::i18n::{message, tr, use_lang};
 use leptos::logging::log;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -6,41 +12,143 @@ use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[component]
-pub fn XlsxForm(title: RwSignal<String>, csp: RwSignal<Option<CardsServerProps>>) -> impl IntoView {
-    let title_row_index = RwSignal::new(None);
-    let sheetname = RwSignal::<String>::new(String::new());
-    let path = RwSignal::<Option<PathBuf>>::new(None);
-    let columns_indexs = RwSignal::<Vec<usize>>::new(Vec::new());
+pub fn XlsxForm(
+    title: RwSignal<String>,
+    csp: RwSignal<Option<CardsServerProps>>,
+    editing: RwSignal<bool>,
+) -> impl IntoView {
+    let old = csp.get_untracked();
+    let title_row_index = RwSignal::new(old.as_ref().and_then(|x| x.title_row_index));
+    let title_rows_count = RwSignal::new(
+        old.as_ref()
+            .map(|x| x.title_rows_count)
+            .unwrap_or(NonZeroUsize::MIN),
+    );
+    let old_source = old.as_ref().map(|x| &x.source);
+    let merged_cells = RwSignal::new(old_source.map(|x| x.merged_cells).unwrap_or_default());
+    let skip_hidden = RwSignal::new(old_source.is_some_and(|x| x.skip_hidden));
+    let area = RwSignal::new(old_source.map(|x| x.area.clone()).unwrap_or_default());
+    let text = RwSignal::new(old_source.map(|x| x.text.clone()).unwrap_or_default());
+    let sheetname =
+        RwSignal::<String>::new(old_source.map(|x| x.sheet.clone()).unwrap_or_default());
+    let path = RwSignal::<Option<PathBuf>>::new(old_source.map(|x| x.path.clone()));
+    let old_back = old.as_ref().and_then(|x| x.back.clone());
+    let has_back = RwSignal::new(old_back.is_some());
+    let back_text = RwSignal::new(
+        old_back
+            .as_ref()
+            .map(|x| x.text.clone())
+            .unwrap_or_default(),
+    );
+    let back_columns = RwSignal::new(old_back.map(|x| x.columns_indexs).unwrap_or_default());
+    let layout = RwSignal::new(old.as_ref().map(|x| x.layout).unwrap_or_default());
+    if old.is_none() {
+        // effects only run in the browser, where the server is asked once.
+        // the answer never replaces a layout the user changed meanwhile
+        Effect::new(move || {
+            leptos::task::spawn_local(async move {
+                if let Ok(default) = default_layout().await {
+                    layout.update(|x| {
+                        if *x == PageLayout::default() {
+                            *x = default;
+                        }
+                    });
+                }
+            });
+        });
+    }
+    let copies = RwSignal::new(old.as_ref().map(|x| x.copies.clone()).unwrap_or_default());
+    let columns_directions = RwSignal::new(
+        old.as_ref()
+            .map(|x| x.columns_directions.clone())
+            .unwrap_or_default(),
+    );
+    let columns_transforms = RwSignal::new(
+        old.as_ref()
+            .map(|x| x.columns_transforms.clone())
+            .unwrap_or_default(),
+    );
+    let format = RwSignal::new(old.as_ref().map(|x| x.format).unwrap_or_default());
+    let rules = RwSignal::new(old.as_ref().map(|x| x.rules.clone()).unwrap_or_default());
+    let exclude_invalid = RwSignal::new(old.as_ref().is_some_and(|x| x.exclude_invalid));
+    let duplicates = RwSignal::new(
+        old.as_ref()
+            .map(|x| x.duplicates.clone())
+            .unwrap_or_default(),
+    );
+    let key_columns = RwSignal::new(duplicates.read_untracked().columns_indexs.clone());
+    let maps = RwSignal::new(old.as_ref().map(|x| x.maps.clone()).unwrap_or_default());
+    let columns_indexs =
+        RwSignal::<Vec<usize>>::new(old.map(|x| x.columns_indexs).unwrap_or_default());
+    let source = Memo::new(move |_| {
+        let sheet = sheetname.get();
+        path.get()
+            .filter(|_| !sheet.is_empty())
+            .map(|path| SheetSource {
+                path,
+                sheet,
+                text: text.get(),
+                area: area.get(),
+                merged_cells: merged_cells.get(),
+                skip_hidden: skip_hidden.get(),
+            })
+    });
+    // the tables and names of a sheet are not those of the next one
+    Effect::watch(
+        move || (path.get(), sheetname.get()),
+        move |_, _, _| area.set(SheetArea::Used),
+        false,
+    );
+    let headers_res = Resource::new(
+        move || (source.get(), title_row_index.get(), title_rows_count.get()),
+        get_headers,
+    );
     let on_submit = move |_| {
-        if let (Some(path), sheet, columns_indexs, false) = (
-            path.get(),
-            sheetname.get(),
-            columns_indexs.get(),
-            title.read().is_empty(),
-        ) && !sheet.is_empty()
+        if let (Some(source), columns_indexs, false) =
+            (source.get(), columns_indexs.get(), title.read().is_empty())
             && !columns_indexs.is_empty()
         {
+            let back = has_back.get().then(|| CardBack {
+                text: back_text.get(),
+                columns_indexs: back_columns.get(),
+            });
             let res = Some(CardsServerProps {
+                source,
                 title_row_index: title_row_index.get(),
-                path,
-                sheet,
+                title_rows_count: title_rows_count.get(),
                 columns_indexs,
+                back,
+                layout: layout.get(),
+                copies: copies.get(),
+                columns_directions: columns_directions.get(),
+                columns_transforms: columns_transforms.get(),
+                format: format.get(),
+                rules: rules.get(),
+                exclude_invalid: exclude_invalid.get(),
+                duplicates: Duplicates {
+                    columns_indexs: key_columns.get(),
+                    ..duplicates.get()
+                },
+                maps: maps.get(),
             });
             csp.set(res);
+            editing.set(false);
         };
     };
     let disabled = move || {
-        path.read().is_none()
-            || sheetname.read().is_empty()
+        source.read().is_none()
             || columns_indexs.read().is_empty()
             || title.read().is_empty()
+            || (has_back.get() && layout.read().rows.is_none())
     };
+    let lang = use_lang();
     let submit_title = move || {
-        if disabled() {
-            "افندم!"
+        let key = if disabled() {
+            "submit-missing"
         } else {
-            "تمام"
-        }
+            "submit"
+        };
+        message(lang.get(), key, &[])
     };
     let submit_style = move || {
         if disabled() {
@@ -54,8 +162,43 @@ pub fn XlsxForm(title: RwSignal<String>, csp: RwSignal<Option<CardsServerProps>>
             <CardTitle title/>
             <XlsxPath path/>
             <SheetName sheetname path/>
-            <TitleRowIndex path sheetname=sheetname index=title_row_index/>
-            <ColumnsIndexs indexs=columns_indexs sheetname=sheetname path headers_index=title_row_index/>
+            <TextOptionsInput text path/>
+            <SheetAreaSelect area sheetname path/>
+            <SkipHidden skip_hidden/>
+            <TitleRowIndex source index=title_row_index rows_count=title_rows_count/>
+            <MergedCellsSelect merged_cells/>
+            <ColumnsIndexs
+                indexs=columns_indexs
+                headers_res
+                directions=columns_directions
+                transforms=columns_transforms
+            />
+            <CardBackInput
+                has_back
+                text=back_text
+                indexs=back_columns
+                headers_res
+            />
+            <CopiesInput
+                copies
+                headers_res
+            />
+            <RulesInput
+                rules
+                exclude_invalid
+                headers_res
+            />
+            <MapsInput
+                maps
+                headers_res
+            />
+            <DuplicatesInput
+                duplicates
+                key_columns
+                headers_res
+            />
+            <ValueFormatInput format/>
+            <PageLayoutInput layout has_back/>
             <button
                 disabled=disabled
                 on:click=on_submit
@@ -76,12 +219,14 @@ fn CardTitle(title: RwSignal<String>) -> impl IntoView {
         }
     };
     view! {
-        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">عنوان الكارت</dd>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("card-title")}</dd>
         <dt>
             <input
                 type="text"
                 style=style
                 class="border-2 w-3/6 rounded-lg p-2 text-center"
+                placeholder=tr("card-title-hint")
+                value=title.get_untracked()
                 on:input:target=move |ev| {
                     let value =ev.target().value();
                     title.set(value.trim().to_string());
@@ -91,70 +236,103 @@ fn CardTitle(title: RwSignal<String>) -> impl IntoView {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Header {
+    index: usize,
+    name: String,
+    sample: String,
+}
+
+/// the headers of the chosen sheet, fetched once for every input of the form
+type HeadersResource = Resource<Result<Vec<Header>, ServerFnError>>;
+
+/// the headers fetched so far, none while they load or after an error
+fn loaded(headers_res: HeadersResource) -> Vec<Header> {
+    headers_res
+        .get()
+        .transpose()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// the name of the column at `index` or its number when it has no header
+fn name_of(headers: &[Header], index: usize) -> String {
+    headers
+        .iter()
+        .find(|x| x.index == index)
+        .map(|x| x.name.clone())
+        .unwrap_or_else(|| (index + 1).to_string())
+}
+
 #[server]
 async fn get_headers(
-    args: (Option<PathBuf>, String, Option<NonZeroUsize>),
-) -> Result<Vec<String>, ServerFnError> {
-    use calamine::{DeError, RangeDeserializerBuilder, Reader, Xlsx, open_workbook};
-    let (path, sheetname, headers_index) = args;
-    let Some(path) = path else {
+    args: (Option<SheetSource>, Option<NonZeroUsize>, NonZeroUsize),
+) -> Result<Vec<Header>, ServerFnError> {
+    use super::sheet::{PREVIEW_CHARS, Sheet, columns_samples, title_row_start};
+    let (source, headers_index, headers_count) = args;
+    let user = crate::auth::current_user()?;
+    let Some(source) = source else {
         return Ok(Vec::new());
     };
-    let mut workbook: Xlsx<_> = open_workbook(&path)?;
-
-    let range = workbook.worksheet_range(&sheetname)?;
-
-    let mut iter = RangeDeserializerBuilder::new()
-        .has_headers(false)
-        .from_range(&range)?;
-
-    let headers: Vec<String> = match headers_index {
-        Some(i) => iter
-            .nth(Into::<usize>::into(i) - 1)
-            .unwrap_or(Err(DeError::HeaderNotFound(format!(
-                "Error number {i} should contain headers"
-            ))))?,
-        None => iter
-            .next()
-            .unwrap_or(Err(DeError::HeaderNotFound(String::from(
-                "Error : first row should contain headers",
-            ))))?,
-    };
+    let headers = (|| {
+        user.check_path(&source.path)?;
+        let sheet = Sheet::open(&source)?;
+
+        let names = sheet.headers(headers_index, headers_count)?;
+        let first_row = title_row_start(headers_index) + headers_count.get();
+        let samples = columns_samples(&sheet.range, first_row, PREVIEW_CHARS);
 
-    Ok(headers)
+        let headers = names
+            .into_iter()
+            .zip(samples)
+            .enumerate()
+            .filter(|(index, _)| sheet.is_column_visible(*index))
+            .map(|(index, (name, sample))| Header {
+                index,
+                name,
+                sample,
+            })
+            .collect::<Vec<_>>();
+        Ok(headers)
+    })();
+    super::audit::record(
+        "get_headers",
+        &source.path,
+        Some(&source.sheet),
+        &headers,
+        |x| Some(x.len()),
+    )?;
+    headers
 }
 
 #[component]
 fn ColumnsIndexs(
     indexs: RwSignal<Vec<usize>>,
-    path: RwSignal<Option<PathBuf>>,
-    sheetname: RwSignal<String>,
-    headers_index: RwSignal<Option<NonZeroUsize>>,
+    headers_res: HeadersResource,
+    /// the message key of the label
+    #[prop(default = "columns")]
+    label: &'static str,
+    /// marks the picker red while no column is chosen
+    #[prop(default = true)]
+    required: bool,
+    /// lets the direction of every chosen column be set
+    #[prop(optional)]
+    directions: Option<RwSignal<Vec<ColumnDirection>>>,
+    /// lets the values of every chosen column be cleaned up
+    #[prop(optional)]
+    transforms: Option<RwSignal<Vec<ColumnTransforms>>>,
 ) -> impl IntoView {
-    let headers_res = Resource::new(
-        move || (path.get(), sheetname.get(), headers_index.get()),
-        get_headers,
-    );
-    let headers = move || {
-        headers_res
-            .get()
-            .transpose()
-            .ok()
-            .flatten()
-            .unwrap_or_default()
-            .into_iter()
-            .enumerate()
-            .collect::<Vec<_>>()
-    };
+    let headers = move || loaded(headers_res);
     let style = move || {
-        if indexs.read().is_empty() {
+        if required && indexs.read().is_empty() {
             "color:red;"
         } else {
             ""
         }
     };
     view! {
-        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">الاعمدة</dd>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr(label)}</dd>
         <dt>
             <dl
                 style=style
@@ -163,16 +341,18 @@ fn ColumnsIndexs(
                 <Suspense>
                     <For
                         each=headers
-                        key=|x| x.1.clone()
-                        let((index,header))
+                        key=|x| (x.index, x.name.clone())
+                        let(Header { index, name, sample })
                     >
                         <div class="grid grid-cols-1 gap-4 border-2 rounded-xl p-3 m-2">
-                            <dd>{header}</dd>
+                            <dd>{name}</dd>
+                            <dd class="text-sm opacity-60">{sample}</dd>
                             <dt>
                                 <input
                                     type="checkbox"
                                     class="w-5 h-5"
                                     value={index}
+                                    prop:checked=move || indexs.read().contains(&index)
                                     on:change:target=move |ev| {
                                         if ev.target().checked() {
                                             indexs.write().push(ev.target().value().parse().unwrap());
@@ -182,6 +362,25 @@ fn ColumnsIndexs(
                                     }
                                 />
                             </dt>
+                            {move || {
+                                let directions = directions?;
+                                indexs.read().contains(&index).then(|| {
+                                    let dir = Signal::derive(move || {
+                                        directions.read().iter().find(|x| x.index == index).map(|x| x.dir)
+                                    });
+                                    let on_change = move |dir: Option<Direction>| directions.update(|x| {
+                                        x.retain(|x| x.index != index);
+                                        x.extend(dir.map(|dir| ColumnDirection { index, dir }));
+                                    });
+                                    view! { <DirectionSelect dir on_change/> }
+                                })
+                            }}
+                            {move || {
+                                let transforms = transforms?;
+                                indexs.read().contains(&index).then(|| view! {
+                                    <TransformsInput index transforms/>
+                                })
+                            }}
                         </div>
                     </For>
                 </Suspense>
@@ -192,12 +391,15 @@ fn ColumnsIndexs(
 
 #[server]
 async fn sheets_names(path: Option<PathBuf>) -> Result<Vec<String>, ServerFnError> {
-    use calamine::{Reader, Xlsx, open_workbook};
+    let user = crate::auth::current_user()?;
     let Some(path) = path else {
         return Ok(Vec::new());
     };
-    let workbook: Xlsx<_> = open_workbook(&path)?;
-    Ok(workbook.sheet_names())
+    let names = user
+        .check_path(&path)
+        .and_then(|_| super::sheet::sheets_names(&path));
+    super::audit::record("sheets_names", &path, None, &names, |x| Some(x.len()))?;
+    names
 }
 
 #[component]
@@ -219,8 +421,15 @@ fn SheetName(sheetname: RwSignal<String>, path: RwSignal<Option<PathBuf>>) -> im
             .flatten()
             .unwrap_or_default()
     };
+    Effect::new(move || {
+        if let [name] = sheets_names().as_slice()
+            && sheetname.read_untracked().is_empty()
+        {
+            sheetname.set(name.clone());
+        }
+    });
     view! {
-        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">اسم الشييت</dd>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("sheet-name")}</dd>
         <dt>
             <select
                 style={style}
@@ -230,14 +439,20 @@ fn SheetName(sheetname: RwSignal<String>, path: RwSignal<Option<PathBuf>>) -> im
                     sheetname.set( value.trim().to_string());
                 }
             >
-                <option>"لا يكن"</option>
+                <option value="">{tr("no-sheet")}</option>
                 <Suspense>
                 <For
                     each=sheets_names
                     key=|x| x.clone()
                     let(name)
                 >
-                    <option value={name.clone()}>{name.clone()}</option>
+                    <option
+                        value={name.clone()}
+                        selected={
+                            let name = name.clone();
+                            move || *sheetname.read() == name
+                        }
+                    >{name.clone()}</option>
                 </For>
                 </Suspense>
             </select>
@@ -245,26 +460,72 @@ fn SheetName(sheetname: RwSignal<String>, path: RwSignal<Option<PathBuf>>) -> im
     }
 }
 
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct RowsPreview {
+    height: usize,
+    previews: Vec<Vec<String>>,
+}
+
 #[server]
-async fn rows_height(args: (Option<PathBuf>, String)) -> Result<usize, ServerFnError> {
-    use calamine::{Reader, Xlsx, open_workbook};
-    let (path, sheetname) = args;
-    let (Some(path), false) = (path, sheetname.is_empty()) else {
-        return Ok(0);
+async fn preview_rows(source: Option<SheetSource>) -> Result<RowsPreview, ServerFnError> {
+    use super::sheet::{PREVIEW_CELLS, PREVIEW_CHARS, PREVIEW_ROWS, Sheet, row_preview};
+    let user = crate::auth::current_user()?;
+    let Some(source) = source else {
+        return Ok(RowsPreview::default());
     };
-    let mut workbook: Xlsx<_> = open_workbook(&path)?;
-    let Ok(range) = workbook.worksheet_range(&sheetname) else {
-        println!("sheet {sheetname} range is empty");
-        return Ok(0);
+    let preview = user.check_path(&source.path).and_then(|_| {
+        let Sheet { range, .. } = Sheet::open(&source)?;
+        let previews = range
+            .rows()
+            .take(PREVIEW_ROWS)
+            .map(|row| row_preview(row, PREVIEW_CELLS, PREVIEW_CHARS))
+            .collect();
+        Ok(RowsPreview {
+            height: range.height(),
+            previews,
+        })
+    });
+    super::audit::record(
+        "preview_rows",
+        &source.path,
+        Some(&source.sheet),
+        &preview,
+        |x| Some(x.previews.len()),
+    )?;
+    preview
+}
+
+#[server]
+async fn guess_title_row(
+    source: Option<SheetSource>,
+) -> Result<Option<NonZeroUsize>, ServerFnError> {
+    use super::sheet::Sheet;
+    let user = crate::auth::current_user()?;
+    let Some(source) = source else {
+        return Ok(None);
     };
-    Ok(range.height())
+    let guess = user
+        .check_path(&source.path)
+        .and_then(|_| Sheet::open(&source))
+        .map(|x| super::sheet::guess_title_row(&x.range));
+    super::audit::record(
+        "guess_title_row",
+        &source.path,
+        Some(&source.sheet),
+        &guess,
+        |_| None,
+    )?;
+    guess
 }
 
+/// the most rows a combined title can span
+const MAX_TITLE_ROWS: usize = 5;
+
 #[component]
 fn TitleRowIndex(
     index: RwSignal<Option<NonZeroUsize>>,
-    path: RwSignal<Option<PathBuf>>,
-    sheetname: RwSignal<String>,
+    rows_count: RwSignal<NonZeroUsize>,
+    source: Memo<Option<SheetSource>>,
 ) -> impl IntoView {
     let style = move || {
         if index.read().is_some() {
@@ -272,14 +533,26 @@ fn TitleRowIndex(
         };
         "color:red;"
     };
-    let rows_height_res = Resource::new(move || (path.get(), sheetname.get()), rows_height);
-    let rows_height = move || rows_height_res.get().transpose().ok().flatten();
+    let rows_preview_res = Resource::new(move || source.get(), preview_rows);
+    let rows_preview = move || rows_preview_res.get().transpose().ok().flatten();
+
+    let guess_res = Resource::new(move || source.get(), guess_title_row);
+    // another file or sheet is guessed again instead of keeping the old row
+    let sheet = Memo::new(move |_| source.get().map(|x| (x.path, x.sheet)));
+    Effect::watch(move || sheet.get(), move |_, _, _| index.set(None), false);
+    Effect::new(move || {
+        if let Some(Ok(Some(guess))) = guess_res.get()
+            && index.get_untracked().is_none()
+        {
+            index.set(Some(guess));
+        }
+    });
     view! {
-        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">مسلسل صف العناوين</dd>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("title-row")}</dd>
         <dt>
             <select
                 style={style}
-                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                class="border-2 w-5/6 rounded-lg p-2 text-center"
                 on:change:target=move |ev| {
                     let value =ev.target().value().parse::<NonZeroUsize>();
                     match value {
@@ -294,17 +567,923 @@ fn TitleRowIndex(
                 }
             >
                 <Suspense>
-                    <ShowLet some=rows_height let(size)>
+                    <ShowLet some=rows_preview let(RowsPreview { height, previews })>
                     {
-                        (1..=size).flat_map(NonZeroUsize::new).map(|i| {
+                        (1..=height).flat_map(NonZeroUsize::new).map(|i| {
+                            let preview = previews
+                                .get(i.get() - 1)
+                                .filter(|x| !x.is_empty())
+                                .map(|x| format!("{i} : {}", x.join(" | ")))
+                                .unwrap_or_else(|| i.to_string());
                             view! {
-                                <option value={i} selected=move || index.read().is_some_and(|x| x == i)>{i}</option>
+                                <option value={i} selected=move || index.read().is_some_and(|x| x == i)>{preview}</option>
                             }
                         }).collect_view()
                     }
                     </ShowLet>
                 </Suspense>
             </select>
+            <Suspense>
+                {move || match rows_preview_res.get() {
+                    Some(Err(e)) => Some(view! { <p class="text-red-600 text-sm m-2">{e.to_string()}</p> }),
+                    _ => None,
+                }}
+            </Suspense>
+        </dt>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("title-rows-count")}</dd>
+        <dt>
+            <select
+                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    if let Ok(value) = ev.target().value().parse::<NonZeroUsize>() {
+                        rows_count.set(value);
+                    }
+                }
+            >
+                {
+                    (1..=MAX_TITLE_ROWS).flat_map(NonZeroUsize::new).map(|i| {
+                        view! {
+                            <option value={i} selected=move || rows_count.get() == i>{i}</option>
+                        }
+                    }).collect_view()
+                }
+            </select>
+        </dt>
+    }
+}
+
+/// encodings offered for delimited text files besides detecting it
+const ENCODINGS: [(&str, &str); 5] = [
+    ("utf-8", "UTF-8"),
+    ("utf-16le", "UTF-16 LE"),
+    ("utf-16be", "UTF-16 BE"),
+    ("windows-1256", "Windows-1256"),
+    ("iso-8859-6", "ISO-8859-6"),
+];
+
+/// delimiters offered for delimited text files besides detecting it
+const DELIMITERS: [(char, &str); 4] = [(',', ","), (';', ";"), ('\t', "Tab"), ('|', "|")];
+
+#[component]
+fn TextOptionsInput(text: RwSignal<TextOptions>, path: RwSignal<Option<PathBuf>>) -> impl IntoView {
+    let is_delimited = move || {
+        path.read()
+            .as_ref()
+            .is_some_and(|x| SourceKind::of(x) == Some(SourceKind::Delimited))
+    };
+    view! {
+        <Show when=is_delimited>
+            <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("encoding")}</dd>
+            <dt>
+                <select
+                    class="border-2 w-2/6 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| {
+                        text.write().encoding = ev.target().value();
+                    }
+                >
+                    <option value="" selected=move || text.read().encoding.is_empty()>{tr("auto")}</option>
+                    {
+                        ENCODINGS.into_iter().map(|(value, label)| {
+                            view! {
+                                <option value={value} selected=move || text.read().encoding == value>{label}</option>
+                            }
+                        }).collect_view()
+                    }
+                </select>
+            </dt>
+            <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("delimiter")}</dd>
+            <dt>
+                <select
+                    class="border-2 w-2/6 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| {
+                        let index = ev.target().value().parse::<usize>().ok();
+                        text.write().delimiter = index.and_then(|i| DELIMITERS.get(i)).map(|x| x.0);
+                    }
+                >
+                    <option value="" selected=move || text.read().delimiter.is_none()>{tr("auto")}</option>
+                    {
+                        DELIMITERS.into_iter().enumerate().map(|(i, (value, label))| {
+                            view! {
+                                <option value={i} selected=move || text.read().delimiter == Some(value)>{label}</option>
+                            }
+                        }).collect_view()
+                    }
+                </select>
+            </dt>
+        </Show>
+    }
+}
+
+#[server]
+async fn sheet_areas(args: (Option<PathBuf>, String)) -> Result<Vec<SheetArea>, ServerFnError> {
+    let user = crate::auth::current_user()?;
+    let (path, sheetname) = args;
+    let (Some(path), false) = (path, sheetname.is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let areas = user
+        .check_path(&path)
+        .and_then(|_| super::sheet::sheet_areas(&path, &sheetname));
+    super::audit::record("sheet_areas", &path, Some(&sheetname), &areas, |x| {
+        Some(x.len())
+    })?;
+    areas
+}
+
+#[component]
+fn SheetAreaSelect(
+    area: RwSignal<SheetArea>,
+    sheetname: RwSignal<String>,
+    path: RwSignal<Option<PathBuf>>,
+) -> impl IntoView {
+    let areas_res = Resource::new(move || (path.get(), sheetname.get()), sheet_areas);
+    let areas = move || {
+        let areas = areas_res
+            .get()
+            .transpose()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        std::iter::once(SheetArea::Used)
+            .chain(areas)
+            .enumerate()
+            .collect::<Vec<_>>()
+    };
+    let lang = use_lang();
+    let label = move |area: &SheetArea| match area {
+        SheetArea::Used => message(lang.get(), "area-used", &[]),
+        SheetArea::Table(name) => message(lang.get(), "area-table", &[("name", name)]),
+        SheetArea::Named(name) => message(
+            lang.get(),
+            "area-named",
+            &[("name", name.trim_start_matches("_xlnm."))],
+        ),
+    };
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("area")}</dd>
+        <dt>
+            <select
+                class="border-2 w-3/6 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    let index = ev.target().value().parse::<usize>().ok();
+                    let value = index.and_then(|i| areas().into_iter().find(|x| x.0 == i));
+                    area.set(value.map(|x| x.1).unwrap_or_default());
+                }
+            >
+                <Suspense>
+                <For
+                    each=areas
+                    key=|x| x.clone()
+                    let((i, value))
+                >
+                    <option
+                        value={i}
+                        selected={
+                            let value = value.clone();
+                            move || *area.read() == value
+                        }
+                    >{
+                        let value = value.clone();
+                        move || label(&value)
+                    }</option>
+                </For>
+                </Suspense>
+            </select>
+        </dt>
+    }
+}
+
+#[component]
+fn SkipHidden(skip_hidden: RwSignal<bool>) -> impl IntoView {
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("skip-hidden")}</dd>
+        <dt>
+            <input
+                type="checkbox"
+                class="w-5 h-5"
+                prop:checked=move || skip_hidden.get()
+                on:change:target=move |ev| skip_hidden.set(ev.target().checked())
+            />
+        </dt>
+    }
+}
+
+#[component]
+fn MergedCellsSelect(merged_cells: RwSignal<MergedCells>) -> impl IntoView {
+    let options = [
+        (MergedCells::Keep, "merged-keep"),
+        (MergedCells::Fill, "merged-fill"),
+        (MergedCells::Prefix, "merged-prefix"),
+    ];
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("merged-cells")}</dd>
+        <dt>
+            <select
+                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    if let Some((value, _)) = ev.target().value().parse::<usize>().ok().and_then(|i| options.get(i)) {
+                        merged_cells.set(*value);
+                    }
+                }
+            >
+                {
+                    options.into_iter().enumerate().map(|(i, (value, label))| {
+                        view! {
+                            <option value={i} selected=move || merged_cells.get() == value>{tr(label)}</option>
+                        }
+                    }).collect_view()
+                }
+            </select>
+        </dt>
+    }
+}
+
+#[component]
+fn CardBackInput(
+    has_back: RwSignal<bool>,
+    text: RwSignal<String>,
+    indexs: RwSignal<Vec<usize>>,
+    headers_res: HeadersResource,
+) -> impl IntoView {
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("card-back")}</dd>
+        <dt>
+            <input
+                type="checkbox"
+                class="w-5 h-5"
+                prop:checked=move || has_back.get()
+                on:change:target=move |ev| has_back.set(ev.target().checked())
+            />
+        </dt>
+        <Show when=move || has_back.get()>
+            <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("back-text")}</dd>
+            <dt>
+                <textarea
+                    class="border-2 w-3/6 rounded-lg p-2"
+                    placeholder=tr("back-text-hint")
+                    prop:value=move || text.get()
+                    on:input:target=move |ev| text.set(ev.target().value())
+                ></textarea>
+            </dt>
+            <ColumnsIndexs
+                indexs
+                headers_res
+                label="back-columns"
+                required=false
+            />
+        </Show>
+    }
+}
+
+#[component]
+fn CopiesInput(copies: RwSignal<Copies>, headers_res: HeadersResource) -> impl IntoView {
+    let headers = move || loaded(headers_res);
+    let lang = use_lang();
+    let fixed = move || match copies.get() {
+        Copies::Fixed(x) => Some(x.get()),
+        Copies::Column(_) => None,
+    };
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("copies")}</dd>
+        <dt class="flex gap-4 place-content-center">
+            <select
+                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    match ev.target().value().parse::<usize>() {
+                        Ok(index) => copies.set(Copies::Column(index)),
+                        Err(_) => copies.set(Copies::default()),
+                    }
+                }
+            >
+                <option value="" selected=move || fixed().is_some()>{tr("copies-fixed")}</option>
+                <Suspense>
+                    {
+                        move || headers().into_iter().map(|Header { index, name, .. }| view! {
+                            <option
+                                value={index}
+                                selected=move || copies.get() == Copies::Column(index)
+                            >{move || message(lang.get(), "copies-column", &[("name", &name)])}</option>
+                        }).collect_view()
+                    }
+                </Suspense>
+            </select>
+            <Show when=move || fixed().is_some()>
+                <input
+                    type="number"
+                    min="1"
+                    max=MAX_COPIES
+                    class="border-2 w-1/6 rounded-lg p-2 text-center"
+                    title=tr("copies-hint")
+                    prop:value=move || fixed().unwrap_or(1)
+                    on:input:target=move |ev| {
+                        if let Ok(count) = ev.target().value().trim().parse::<NonZeroUsize>() {
+                            copies.set(Copies::Fixed(count));
+                        }
+                    }
+                />
+            </Show>
+        </dt>
+    }
+}
+
+/// the kinds of rules in the order of their select, by message key
+const RULE_KINDS: [&str; 6] = [
+    "rule-required",
+    "rule-regex",
+    "rule-length",
+    "rule-range",
+    "rule-unique",
+    "rule-allowed",
+];
+
+/// the rule of `kind` with the settings written in the form, `None` while
+/// they are missing or not numbers
+fn new_rule(kind: &str, first: &str, second: &str) -> Option<Rule> {
+    let (first, second) = (first.trim(), second.trim());
+    fn bound<T: std::str::FromStr>(x: &str) -> Option<Option<T>> {
+        if x.is_empty() {
+            Some(None)
+        } else {
+            x.parse().ok().map(Some)
+        }
+    }
+    let rule = match kind {
+        "rule-required" => Rule::Required,
+        "rule-regex" if !first.is_empty() => Rule::Regex(first.to_string()),
+        "rule-length" => Rule::Length {
+            min: bound(first)?,
+            max: bound(second)?,
+        },
+        "rule-range" => Rule::Range {
+            min: bound(first)?,
+            max: bound(second)?,
+        },
+        "rule-unique" => Rule::Unique,
+        "rule-allowed" => Rule::Allowed(
+            first
+                .split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect(),
+        ),
+        _ => return None,
+    };
+    Some(rule)
+}
+
+#[component]
+fn RulesInput(
+    rules: RwSignal<Vec<ColumnRule>>,
+    exclude_invalid: RwSignal<bool>,
+    headers_res: HeadersResource,
+) -> impl IntoView {
+    let headers = move || loaded(headers_res);
+    let column = RwSignal::new(None::<usize>);
+    let kind = RwSignal::new(RULE_KINDS[0]);
+    let first = RwSignal::new(String::new());
+    let second = RwSignal::new(String::new());
+    let bounded = move || matches!(kind.get(), "rule-length" | "rule-range");
+    let first_hint = move || match kind.get() {
+        "rule-regex" => "rule-regex-hint",
+        "rule-allowed" => "rule-allowed-hint",
+        _ => "rule-min",
+    };
+    let add = move |_| {
+        let Some(index) = column.get_untracked() else {
+            return;
+        };
+        let rule = new_rule(
+            kind.get_untracked(),
+            &first.read_untracked(),
+            &second.read_untracked(),
+        );
+        if let Some(rule) = rule {
+            rules.update(|x| x.push(ColumnRule { index, rule }));
+            first.set(String::new());
+            second.set(String::new());
+        }
+    };
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("rules")}</dd>
+        <dt class="flex flex-col gap-2 items-center">
+            <Suspense>
+                {move || rules.get().into_iter().enumerate().map(|(i, ColumnRule { index, rule })| view! {
+                    <div class="flex gap-2 items-center">
+                        <bdi class="font-bold">{name_of(&headers(), index)}</bdi>
+                        <span>{tr(rule.label())}</span>
+                        <bdi dir="ltr">{rule.detail()}</bdi>
+                        <button
+                            on:click=move |_| rules.update(|x| { x.remove(i); })
+                            class="border-2 rounded-lg px-2 hover:cursor-pointer"
+                        >"✕"</button>
+                    </div>
+                }).collect_view()}
+            </Suspense>
+            <div class="flex flex-wrap gap-2 items-center place-content-center">
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| column.set(ev.target().value().parse().ok())
+                >
+                    <option value="">{tr("rule-column")}</option>
+                    <Suspense>
+                        {move || headers().into_iter().map(|Header { index, name, .. }| view! {
+                            <option value={index} selected=move || column.get() == Some(index)>{name}</option>
+                        }).collect_view()}
+                    </Suspense>
+                </select>
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| {
+                        if let Some(x) = ev.target().value().parse::<usize>().ok().and_then(|i| RULE_KINDS.get(i)) {
+                            kind.set(x);
+                        }
+                    }
+                >
+                    {RULE_KINDS.into_iter().enumerate().map(|(i, label)| view! {
+                        <option value={i} selected=move || kind.get() == label>{tr(label)}</option>
+                    }).collect_view()}
+                </select>
+                <Show when=move || !matches!(kind.get(), "rule-required" | "rule-unique")>
+                    <input
+                        type="text"
+                        dir="ltr"
+                        class="border-2 rounded-lg p-2 w-40"
+                        placeholder=move || tr(first_hint())()
+                        prop:value=move || first.get()
+                        on:input:target=move |ev| first.set(ev.target().value())
+                    />
+                </Show>
+                <Show when=bounded>
+                    <input
+                        type="text"
+                        dir="ltr"
+                        class="border-2 rounded-lg p-2 w-24"
+                        placeholder=tr("rule-max")
+                        prop:value=move || second.get()
+                        on:input:target=move |ev| second.set(ev.target().value())
+                    />
+                </Show>
+                <button
+                    on:click=add
+                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                >{tr("rule-add")}</button>
+            </div>
+            <label class="flex gap-2 items-center">
+                <input
+                    type="checkbox"
+                    class="w-5 h-5"
+                    prop:checked=move || exclude_invalid.get()
+                    on:change:target=move |ev| exclude_invalid.set(ev.target().checked())
+                />
+                {tr("exclude-invalid")}
+            </label>
+        </dt>
+    }
+}
+
+/// the entries of an inline map written one `value = label` on every line,
+/// lines without `=` are left out
+fn parse_entries(text: &str) -> Vec<MapEntry> {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(value, label)| MapEntry {
+            value: value.trim().to_string(),
+            label: label.trim().to_string(),
+        })
+        .filter(|x| !x.value.is_empty())
+        .collect()
+}
+
+#[component]
+fn MapsInput(maps: RwSignal<Vec<ColumnMap>>, headers_res: HeadersResource) -> impl IntoView {
+    let headers = move || loaded(headers_res);
+    let column = RwSignal::new(None::<usize>);
+    let from_sheet = RwSignal::new(false);
+    let entries = RwSignal::new(String::new());
+    let map_path = RwSignal::new(None::<PathBuf>);
+    let map_sheet = RwSignal::new(String::new());
+    let sheets_res = Resource::new(move || map_path.get(), sheets_names);
+    let sheets = move || {
+        sheets_res
+            .get()
+            .transpose()
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    };
+    let add = move |_| {
+        let Some(index) = column.get_untracked() else {
+            return;
+        };
+        let map = if from_sheet.get_untracked() {
+            let (Some(path), sheet) = (map_path.get_untracked(), map_sheet.get_untracked()) else {
+                return;
+            };
+            // a csv or a workbook of one sheet is never picked in the select
+            let sheet = if sheet.is_empty() {
+                sheets_res
+                    .get_untracked()
+                    .and_then(Result::ok)
+                    .and_then(|x| x.into_iter().next())
+            } else {
+                Some(sheet)
+            };
+            let Some(sheet) = sheet else {
+                return;
+            };
+            ValueMap::Sheet(SheetSource {
+                path,
+                sheet,
+                text: TextOptions::default(),
+                area: SheetArea::Used,
+                merged_cells: MergedCells::Keep,
+                skip_hidden: false,
+            })
+        } else {
+            let parsed = parse_entries(&entries.read_untracked());
+            if parsed.is_empty() {
+                return;
+            }
+            ValueMap::Inline(parsed)
+        };
+        maps.update(|x| x.push(ColumnMap { index, map }));
+        entries.set(String::new());
+    };
+    let lang = use_lang();
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("maps")}</dd>
+        <dt class="flex flex-col gap-2 items-center">
+            <Suspense>
+                {move || maps.get().into_iter().enumerate().map(|(i, ColumnMap { index, map })| {
+                    let detail = match map {
+                        ValueMap::Inline(entries) => {
+                            let count = entries.len().to_string();
+                            view! {
+                                <span>{move || message(lang.get(), "map-values", &[("count", &count)])}</span>
+                            }.into_any()
+                        }
+                        ValueMap::Sheet(source) => view! {
+                            <bdi dir="ltr">{source.path.display().to_string()}</bdi>
+                            <bdi>{source.sheet}</bdi>
+                        }.into_any(),
+                    };
+                    view! {
+                        <div class="flex gap-2 items-center">
+                            <bdi class="font-bold">{name_of(&headers(), index)}</bdi>
+                            {detail}
+                            <button
+                                on:click=move |_| maps.update(|x| { x.remove(i); })
+                                class="border-2 rounded-lg px-2 hover:cursor-pointer"
+                            >"✕"</button>
+                        </div>
+                    }
+                }).collect_view()}
+            </Suspense>
+            <div class="flex flex-wrap gap-2 items-center place-content-center">
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| column.set(ev.target().value().parse().ok())
+                >
+                    <option value="">{tr("rule-column")}</option>
+                    <Suspense>
+                        {move || headers().into_iter().map(|Header { index, name, .. }| view! {
+                            <option value={index} selected=move || column.get() == Some(index)>{name}</option>
+                        }).collect_view()}
+                    </Suspense>
+                </select>
+                <select
+                    class="border-2 rounded-lg p-2 text-center"
+                    on:change:target=move |ev| from_sheet.set(ev.target().value() == "sheet")
+                >
+                    <option value="inline" selected=move || !from_sheet.get()>{tr("map-inline")}</option>
+                    <option value="sheet" selected=move || from_sheet.get()>{tr("map-sheet")}</option>
+                </select>
+                <button
+                    on:click=add
+                    class="border-2 rounded-lg p-2 hover:cursor-pointer"
+                >{tr("map-add")}</button>
+            </div>
+            <Show
+                when=move || from_sheet.get()
+                fallback=move || view! {
+                    <textarea
+                        dir="auto"
+                        class="border-2 w-3/6 rounded-lg p-2"
+                        placeholder=tr("map-inline-hint")
+                        prop:value=move || entries.get()
+                        on:input:target=move |ev| entries.set(ev.target().value())
+                    ></textarea>
+                }
+            >
+                <div class="flex flex-wrap gap-2 items-center place-content-center">
+                    <input
+                        type="text"
+                        dir="ltr"
+                        class="border-2 rounded-lg p-2 w-80"
+                        placeholder=tr("map-path-hint")
+                        on:change:target=move |ev| {
+                            let value = ev.target().value();
+                            map_path.set(Some(PathBuf::from(value.trim())).filter(|x| !x.as_os_str().is_empty()));
+                            map_sheet.set(String::new());
+                        }
+                    />
+                    <select
+                        class="border-2 rounded-lg p-2 text-center"
+                        on:change:target=move |ev| map_sheet.set(ev.target().value())
+                    >
+                        <Suspense>
+                            {move || sheets().into_iter().map(|name| view! {
+                                <option value=name.clone()>{name.clone()}</option>
+                            }).collect_view()}
+                        </Suspense>
+                    </select>
+                </div>
+            </Show>
+        </dt>
+    }
+}
+
+/// the key columns of a card set, how their values are matched and which
+/// duplicates make cards
+#[component]
+fn DuplicatesInput(
+    duplicates: RwSignal<Duplicates>,
+    key_columns: RwSignal<Vec<usize>>,
+    headers_res: HeadersResource,
+) -> impl IntoView {
+    const MATCHING: &[(KeyMatching, &str)] = &[
+        (KeyMatching::Exact, "matching-exact"),
+        (KeyMatching::Normalized, "matching-normalized"),
+    ];
+    const KEEP: &[(KeepDuplicates, &str)] = &[
+        (KeepDuplicates::All, "keep-all"),
+        (KeepDuplicates::First, "keep-first"),
+        (KeepDuplicates::Last, "keep-last"),
+    ];
+    view! {
+        <ColumnsIndexs
+            indexs=key_columns
+            headers_res
+            label="duplicate-keys"
+            required=false
+        />
+        <Show when=move || !key_columns.read().is_empty()>
+            <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("duplicates")}</dd>
+            <dt class="flex gap-4 place-content-center">
+                {choice_select(
+                    MATCHING,
+                    Signal::derive(move || duplicates.read().matching),
+                    move |x| duplicates.update(|d| d.matching = x),
+                )}
+                {choice_select(
+                    KEEP,
+                    Signal::derive(move || duplicates.read().keep),
+                    move |x| duplicates.update(|d| d.keep = x),
+                )}
+            </dt>
+        </Show>
+    }
+}
+
+/// a choice between detecting the direction and a fixed one
+#[component]
+fn DirectionSelect(
+    dir: Signal<Option<Direction>>,
+    on_change: impl Fn(Option<Direction>) + 'static,
+) -> impl IntoView {
+    let options = [
+        (None, "dir-auto"),
+        (Some(Direction::Rtl), "dir-rtl"),
+        (Some(Direction::Ltr), "dir-ltr"),
+    ];
+    view! {
+        <select
+            class="border-2 rounded-lg p-1 text-center text-sm"
+            on:change:target=move |ev| {
+                if let Some((value, _)) = ev.target().value().parse::<usize>().ok().and_then(|i| options.get(i)) {
+                    on_change(*value);
+                }
+            }
+        >
+            {
+                options.into_iter().enumerate().map(|(i, (value, label))| {
+                    view! {
+                        <option value={i} selected=move || dir.get() == value>{tr(label)}</option>
+                    }
+                }).collect_view()
+            }
+        </select>
+    }
+}
+
+/// the transforms of the column at `index`, folded while none is chosen
+#[component]
+fn TransformsInput(index: usize, transforms: RwSignal<Vec<ColumnTransforms>>) -> impl IntoView {
+    type Flag = fn(&mut Transforms) -> &mut bool;
+    const FLAGS: [(&str, Flag); 7] = [
+        ("strip-tashkeel", |x| &mut x.strip_tashkeel),
+        ("remove-tatweel", |x| &mut x.remove_tatweel),
+        ("unify-letters", |x| &mut x.unify_letters),
+        ("collapse-spaces", |x| &mut x.collapse_spaces),
+        ("trim", |x| &mut x.trim),
+        ("title-case", |x| &mut x.title_case),
+        ("ungrouped", |x| &mut x.ungrouped),
+    ];
+    let current = move || {
+        transforms
+            .read()
+            .iter()
+            .find(|x| x.index == index)
+            .map(|x| x.transforms)
+            .unwrap_or_default()
+    };
+    let change = move |edit: &dyn Fn(&mut Transforms)| {
+        let mut changed = current();
+        edit(&mut changed);
+        transforms.update(|x| {
+            x.retain(|x| x.index != index);
+            if !changed.is_empty() {
+                x.push(ColumnTransforms {
+                    index,
+                    transforms: changed,
+                });
+            }
+        });
+    };
+    view! {
+        <details class="text-sm text-start" open=!current().is_empty()>
+            <summary class="hover:cursor-pointer">{tr("transforms")}</summary>
+            {FLAGS.map(|(label, flag)| view! {
+                <label class="flex gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || *flag(&mut current())
+                        on:change:target=move |ev| {
+                            let checked = ev.target().checked();
+                            change(&|x| *flag(x) = checked);
+                        }
+                    />
+                    {tr(label)}
+                </label>
+            })}
+            <label class="flex gap-2 items-center">
+                {tr("max-length")}
+                <input
+                    type="number"
+                    min="1"
+                    class="border-2 rounded-lg p-1 w-16 text-center"
+                    prop:value=move || current().max_length.map(|x| x.to_string()).unwrap_or_default()
+                    on:change:target=move |ev| {
+                        let max = ev.target().value().trim().parse::<NonZeroUsize>().ok();
+                        change(&|x| x.max_length = max);
+                    }
+                />
+            </label>
+        </details>
+    }
+}
+
+/// a select of `options` showing the label of each one
+fn choice_select<T: Copy + PartialEq + Send + Sync + 'static>(
+    options: &'static [(T, &'static str)],
+    value: Signal<T>,
+    on_change: impl Fn(T) + 'static,
+) -> impl IntoView {
+    view! {
+        <select
+            class="border-2 w-2/6 rounded-lg p-2 text-center"
+            on:change:target=move |ev| {
+                if let Some((x, _)) = ev.target().value().parse::<usize>().ok().and_then(|i| options.get(i)) {
+                    on_change(*x);
+                }
+            }
+        >
+            {
+                options.iter().enumerate().map(|(i, (x, label))| {
+                    let x = *x;
+                    view! {
+                        <option value={i} selected=move || value.get() == x>{tr(label)}</option>
+                    }
+                }).collect_view()
+            }
+        </select>
+    }
+}
+
+#[component]
+fn ValueFormatInput(format: RwSignal<ValueFormat>) -> impl IntoView {
+    const DIGITS: &[(Digits, &str)] = &[
+        (Digits::Western, "digits-western"),
+        (Digits::ArabicIndic, "digits-arabic"),
+    ];
+    const DATES: &[(DateLocale, &str)] = &[
+        (DateLocale::AsStored, "date-as-stored"),
+        (DateLocale::Numeric, "date-numeric"),
+        (DateLocale::Arabic, "date-arabic"),
+        (DateLocale::English, "date-english"),
+    ];
+    const CALENDARS: &[(Calendar, &str)] = &[
+        (Calendar::Gregorian, "calendar-gregorian"),
+        (Calendar::Hijri, "calendar-hijri"),
+    ];
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("digits")}</dd>
+        <dt>
+            {choice_select(
+                DIGITS,
+                Signal::derive(move || format.read().digits),
+                move |x| format.update(|f| f.digits = x),
+            )}
+            <label class="flex gap-2 justify-center items-center m-2">
+                <input
+                    type="checkbox"
+                    class="w-5 h-5"
+                    prop:checked=move || format.read().thousands
+                    on:change:target=move |ev| format.update(|f| f.thousands = ev.target().checked())
+                />
+                {tr("thousands")}
+            </label>
+        </dt>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("date-format")}</dd>
+        <dt>
+            {choice_select(
+                DATES,
+                Signal::derive(move || format.read().date),
+                move |x| format.update(|f| f.date = x),
+            )}
+        </dt>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("calendar")}</dd>
+        <dt>
+            {choice_select(
+                CALENDARS,
+                Signal::derive(move || format.read().calendar),
+                move |x| format.update(|f| f.calendar = x),
+            )}
+        </dt>
+    }
+}
+
+/// the page layout of a new card set set in the server configuration
+#[server]
+async fn default_layout() -> Result<PageLayout, ServerFnError> {
+    Ok(crate::config::config().layout)
+}
+
+#[component]
+fn PageLayoutInput(layout: RwSignal<PageLayout>, has_back: RwSignal<bool>) -> impl IntoView {
+    // backs are printed behind their fronts only on pages of a known size
+    let rows_style = move || {
+        if has_back.get() && layout.read().rows.is_none() {
+            "color:red;"
+        } else {
+            ""
+        }
+    };
+    view! {
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("cards-direction")}</dd>
+        <dt>
+            <DirectionSelect
+                dir=Signal::derive(move || layout.read().dir)
+                on_change={move |dir| layout.update(|x| x.dir = dir)}
+            />
+        </dt>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("page-columns")}</dd>
+        <dt>
+            <select
+                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                on:change:target=move |ev| {
+                    if let Ok(columns) = ev.target().value().parse::<NonZeroUsize>() {
+                        layout.update(|x| x.columns = columns);
+                    }
+                }
+            >
+                {
+                    (1..=MAX_PAGE_COLUMNS).flat_map(NonZeroUsize::new).map(|i| {
+                        view! {
+                            <option value={i} selected=move || layout.read().columns == i>{i}</option>
+                        }
+                    }).collect_view()
+                }
+            </select>
+        </dt>
+        <dd
+            class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl"
+            style=rows_style
+        >{tr("page-rows")}</dd>
+        <dt>
+            <input
+                type="number"
+                min="1"
+                max=MAX_PAGE_ROWS
+                placeholder=tr("auto")
+                title=tr("page-rows-hint")
+                class="border-2 w-2/6 rounded-lg p-2 text-center"
+                prop:value=move || layout.read().rows.map(|x| x.to_string()).unwrap_or_default()
+                on:input:target=move |ev| {
+                    let rows = ev.target().value().trim().parse::<NonZeroUsize>().ok();
+                    layout.update(|x| x.rows = rows);
+                }
+            />
         </dt>
     }
 }
@@ -318,51 +1497,77 @@ enum PathExisting {
 
 #[server]
 async fn path_exists(path: PathBuf) -> Result<PathExisting, ServerFnError> {
-    let res = if path.exists() {
-        PathExisting::Exists(path)
+    let user = crate::auth::current_user()?;
+    let res = if !user.may_read(&path) {
+        PathExisting::None
+    } else if path.exists() {
+        PathExisting::Exists(path.clone())
     } else if path.parent().is_some_and(|x| x.exists()) {
-        PathExisting::ParentExists(path)
+        PathExisting::ParentExists(path.clone())
     } else {
         PathExisting::None
     };
-    Ok(res)
+    let res = Ok(res);
+    super::audit::record("path_exists", &path, None, &res, |_| None)?;
+    res
 }
 
 #[server]
 async fn path_autocomplete(path: PathExisting) -> Result<Vec<PathBuf>, ServerFnError> {
-    match path {
-        PathExisting::Exists(path) => {
-            let mut enteries = tokio::fs::read_dir(&path).await?;
-            let mut paths = Vec::new();
-            while let Some(entry) = enteries.next_entry().await? {
-                paths.push(entry.path());
+    let user = crate::auth::current_user()?;
+    let (PathExisting::Exists(asked) | PathExisting::ParentExists(asked)) = &path else {
+        return Ok(Vec::new());
+    };
+    let asked = asked.clone();
+    let paths = async {
+        let paths = match path {
+            PathExisting::Exists(path) => {
+                user.check_path(&path)?;
+                let mut enteries = tokio::fs::read_dir(&path).await?;
+                let mut paths = Vec::new();
+                while let Some(entry) = enteries.next_entry().await? {
+                    paths.push(entry.path());
+                }
+                paths
             }
-            Ok(paths)
-        }
-        PathExisting::ParentExists(path) => {
-            let parent = path.parent().unwrap();
-            let name = path.file_name().unwrap().to_str().unwrap().to_lowercase();
-            let mut enteries = tokio::fs::read_dir(&parent).await?;
-            let mut paths = Vec::new();
-            while let Some(entry) = enteries.next_entry().await? {
-                let epath = entry.path();
-                if epath
-                    .file_name()
-                    .and_then(|x| x.to_str())
-                    .is_some_and(|x| x.to_lowercase().starts_with(&name))
-                {
-                    paths.push(epath);
+            PathExisting::ParentExists(path) => {
+                // a root has no parent and a name that is not utf-8 matches nothing
+                let (Some(parent), Some(name)) =
+                    (path.parent(), path.file_name().and_then(|x| x.to_str()))
+                else {
+                    return Ok(Vec::new());
+                };
+                user.check_path(parent)?;
+                let name = name.to_lowercase();
+                let mut enteries = tokio::fs::read_dir(&parent).await?;
+                let mut paths = Vec::new();
+                while let Some(entry) = enteries.next_entry().await? {
+                    let epath = entry.path();
+                    if epath
+                        .file_name()
+                        .and_then(|x| x.to_str())
+                        .is_some_and(|x| x.to_lowercase().starts_with(&name))
+                    {
+                        paths.push(epath);
+                    }
                 }
+                paths
             }
-            Ok(paths)
-        }
-        PathExisting::None => Ok(Vec::new()),
+            PathExisting::None => Vec::new(),
+        };
+        Ok(paths
+            .into_iter()
+            .filter(|x| user.may_read(x))
+            .collect::<Vec<_>>())
     }
+    .await;
+    super::audit::record("path_autocomplete", &asked, None, &paths, |x| Some(x.len()))?;
+    paths
 }
 
 #[component]
 fn XlsxPath(path: RwSignal<Option<PathBuf>>) -> impl IntoView {
-    let input_path = RwSignal::new(PathBuf::new());
+    let input_path = RwSignal::new(path.get_untracked().unwrap_or_default());
     let style = RwSignal::new("");
 
     let input_path_exists_res = Resource::new(move || input_path.get(), path_exists);
@@ -388,11 +1593,9 @@ fn XlsxPath(path: RwSignal<Option<PathBuf>>) -> impl IntoView {
     Effect::new(move || {
         let input_path = input_path.get_untracked();
 
-        let is_excel = input_path
-            .extension()
-            .is_some_and(|x| ["xls", "xlsx", "xlsb", "ods"].contains(&x.to_str().unwrap_or("")));
+        let is_source = SourceKind::of(&input_path).is_some();
 
-        if matches!(input_path_exists(), PathExisting::Exists(_)) && is_excel {
+        if matches!(input_path_exists(), PathExisting::Exists(_)) && is_source {
             path.set(Some(input_path));
             style.set("");
         } else {
@@ -401,7 +1604,7 @@ fn XlsxPath(path: RwSignal<Option<PathBuf>>) -> impl IntoView {
     });
 
     view! {
-        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">موقع ملف الاكسل</dd>
+        <dd class="text-2xl m-2 p-2 font-bold border-l-2 border-r-2 rounded-xl">{tr("path")}</dd>
         <dt>
             <input
                 dir="ltr"
@@ -409,6 +1612,7 @@ fn XlsxPath(path: RwSignal<Option<PathBuf>>) -> impl IntoView {
                 class="border-2 w-5/6 rounded-lg p-3 text-center"
                 list="paths"
                 style=style
+                value=input_path.get_untracked().display().to_string()
                 on:input:target=move |ev| {
                     let value =ev.target().value().parse::<PathBuf>();
                     let Ok(value) = value;
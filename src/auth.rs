@@ -0,0 +1,371 @@
+//! local accounts with salted password hashes kept in a json file and the
+//! session cookies of the users logged in with them. every request but the
+//! login page and the static assets needs a session.
+
+use crate::config::config;
+use axum::{
+    extract::Request,
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use base64::{
+    Engine, engine::general_purpose::STANDARD_NO_PAD, engine::general_purpose::URL_SAFE_NO_PAD,
+};
+use leptos::prelude::ServerFnError;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+/// the accounts file in the data directory
+const USERS_FILE: &str = "users.json";
+const COOKIE: &str = "session";
+/// how long a login lasts, sessions are kept in memory so a restart ends them too
+const SESSION_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// rounds of pbkdf2 for new passwords, the stored hash keeps its own count
+const ITERATIONS: u32 = 600_000;
+
+/// the requests that work without logging in
+const PUBLIC_PATHS: [&str; 3] = ["/login", "/api/login", "/favicon.ico"];
+const PUBLIC_PREFIXES: [&str; 1] = ["/pkg/"];
+
+#[derive(Serialize, Deserialize)]
+struct Account {
+    name: String,
+    /// `pbkdf2-sha256$iterations$salt$hash` with the salt and hash in base64
+    password: String,
+    #[serde(default)]
+    admin: bool,
+    /// the directory holding the files of the user, any file when `None`
+    #[serde(default)]
+    root: Option<PathBuf>,
+}
+
+/// the user a request was made by
+#[derive(Clone, Debug)]
+pub struct User {
+    pub name: String,
+    /// sees the files and history of every user
+    pub admin: bool,
+    pub root: Option<PathBuf>,
+}
+
+struct Session {
+    user: User,
+    expires: Instant,
+}
+
+static SESSIONS: LazyLock<Mutex<HashMap<String, Session>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn users_path() -> PathBuf {
+    config().data_dir.join(USERS_FILE)
+}
+
+fn accounts() -> Result<Vec<Account>, String> {
+    let path = users_path();
+    match std::fs::read_to_string(&path) {
+        Ok(x) => {
+            serde_json::from_str(&x).map_err(|e| format!("{} is not valid: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("can not read {}: {e}", path.display())),
+    }
+}
+
+/// creates the account `name` or changes its password, role and directory
+pub fn add_user(
+    name: &str,
+    password: &str,
+    admin: bool,
+    root: Option<PathBuf>,
+) -> Result<(), String> {
+    if name.trim().is_empty() || password.is_empty() {
+        return Err("the name and the password can not be empty".to_string());
+    }
+    // the name is the directory of the user under the upload directory
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err("the name can not hold slashes or start with a dot".to_string());
+    }
+    let root = root
+        .map(|x| {
+            x.canonicalize()
+                .map_err(|e| format!("can not use {} as the directory: {e}", x.display()))
+        })
+        .transpose()?;
+    let mut accounts = accounts()?;
+    accounts.retain(|x| x.name != name);
+    accounts.push(Account {
+        name: name.to_string(),
+        password: hash_password(password)?,
+        admin,
+        root,
+    });
+    let json = serde_json::to_string_pretty(&accounts).map_err(|e| e.to_string())?;
+    let path = users_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("can not create {}: {e}", dir.display()))?;
+    }
+    std::fs::write(&path, json).map_err(|e| format!("can not write {}: {e}", path.display()))
+}
+
+/// `adduser NAME [--admin] [--root DIR]` with the password read from stdin
+pub fn add_user_command(args: &[String]) -> Result<(), String> {
+    const USAGE: &str = "usage: kvg adduser NAME [--admin] [--root DIR] < password";
+    let mut args = args.iter();
+    let name = args.next().ok_or(USAGE)?;
+    let (mut admin, mut root) = (false, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--admin" => admin = true,
+            "--root" => root = Some(PathBuf::from(args.next().ok_or(USAGE)?)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let mut password = String::new();
+    std::io::stdin()
+        .read_line(&mut password)
+        .map_err(|e| format!("can not read the password: {e}"))?;
+    add_user(name, password.trim_end_matches(['\r', '\n']), admin, root)
+}
+
+/// pbkdf2 with hmac-sha256, as long as the hash itself
+fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(password, salt, iterations)
+}
+
+fn hash_password(password: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    getrandom::fill(&mut salt).map_err(|e| format!("no randomness for the salt: {e}"))?;
+    let hash = pbkdf2(password.as_bytes(), &salt, ITERATIONS);
+    Ok(format!(
+        "pbkdf2-sha256${ITERATIONS}${}${}",
+        STANDARD_NO_PAD.encode(salt),
+        STANDARD_NO_PAD.encode(hash)
+    ))
+}
+
+fn verify_password(password: &str, stored: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some("pbkdf2-sha256"), Some(iterations), Some(salt), Some(hash), None) = (
+        parts.next(),
+        parts.next().and_then(|x| x.parse::<u32>().ok()),
+        parts.next().and_then(|x| STANDARD_NO_PAD.decode(x).ok()),
+        parts.next().and_then(|x| STANDARD_NO_PAD.decode(x).ok()),
+        parts.next(),
+    ) else {
+        return false;
+    };
+    let computed = pbkdf2(password.as_bytes(), &salt, iterations);
+    // compared in constant time to not tell how much of the hash matched
+    hash.len() == computed.len() && hash.iter().zip(computed).fold(0, |x, (a, b)| x | (a ^ b)) == 0
+}
+
+/// the hash checked when no account has the name, so that a wrong name takes
+/// as long as a wrong password and does not tell which names exist
+static MISSING_ACCOUNT: LazyLock<String> = LazyLock::new(|| hash_password("").unwrap_or_default());
+
+/// a new session token when the password of `name` is right
+pub async fn login(name: &str, password: &str) -> Result<Option<String>, ServerFnError> {
+    let account = accounts()
+        .map_err(ServerFnError::new)?
+        .into_iter()
+        .find(|x| x.name == name);
+    let stored = match &account {
+        Some(x) => x.password.clone(),
+        None => MISSING_ACCOUNT.clone(),
+    };
+    let password = password.to_string();
+    // the hash takes a while, it is kept off the threads serving requests
+    let right = tokio::task::spawn_blocking(move || verify_password(&password, &stored))
+        .await
+        .map_err(ServerFnError::new)?;
+    let Some(account) = account.filter(|_| right) else {
+        return Ok(None);
+    };
+    // users without a directory of their own get one under the upload directory
+    let root = match account.root {
+        None if !account.admin => config()
+            .upload_dir
+            .as_ref()
+            .map(|dir| {
+                let dir = dir.join(&account.name);
+                std::fs::create_dir_all(&dir).map(|_| dir)
+            })
+            .transpose()?,
+        root => root,
+    };
+    let mut token = [0u8; 32];
+    getrandom::fill(&mut token).map_err(ServerFnError::new)?;
+    let token = URL_SAFE_NO_PAD.encode(token);
+    let session = Session {
+        user: User {
+            name: account.name,
+            admin: account.admin,
+            root,
+        },
+        expires: Instant::now() + SESSION_AGE,
+    };
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|x| x.into_inner());
+    sessions.retain(|_, x| x.expires > Instant::now());
+    sessions.insert(token.clone(), session);
+    Ok(Some(token))
+}
+
+pub fn logout(token: &str) {
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|x| x.into_inner())
+        .remove(token);
+}
+
+/// the `set-cookie` value starting a session
+pub fn session_cookie(token: &str) -> String {
+    format!(
+        "{COOKIE}={token}; path=/; max-age={}; HttpOnly; SameSite=Lax",
+        SESSION_AGE.as_secs()
+    )
+}
+
+/// the `set-cookie` value ending the session
+pub fn cleared_cookie() -> String {
+    format!("{COOKIE}=; path=/; max-age=0; HttpOnly; SameSite=Lax")
+}
+
+/// the session token sent with a request
+pub fn request_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|x| x.to_str().ok())
+        .flat_map(|x| x.split(';'))
+        .filter_map(|x| x.trim().split_once('='))
+        .find(|(name, _)| *name == COOKIE)
+        .map(|(_, value)| value)
+}
+
+fn session_user(token: &str) -> Option<User> {
+    let sessions = SESSIONS.lock().unwrap_or_else(|x| x.into_inner());
+    sessions
+        .get(token)
+        .filter(|x| x.expires > Instant::now())
+        .map(|x| x.user.clone())
+}
+
+/// puts the [`User`] of the session in the extensions of the request, sending
+/// pages to the login page and refusing anything else without one
+pub async fn require_login(mut req: Request, next: Next) -> Response {
+    let user = request_token(req.headers()).and_then(session_user);
+    let path = req.uri().path();
+    let public =
+        PUBLIC_PATHS.contains(&path) || PUBLIC_PREFIXES.iter().any(|x| path.starts_with(x));
+    match user {
+        Some(user) => {
+            req.extensions_mut().insert(user);
+            next.run(req).await
+        }
+        None if public => next.run(req).await,
+        None if req.method() == Method::GET
+            && !path.starts_with("/api/")
+            && !path.starts_with("/events/") =>
+        {
+            Redirect::to("/login").into_response()
+        }
+        None => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// the user calling a server function
+pub fn current_user() -> Result<User, ServerFnError> {
+    leptos::prelude::use_context::<axum::http::request::Parts>()
+        .and_then(|x| x.extensions.get::<User>().cloned())
+        .ok_or_else(|| ServerFnError::new("login required"))
+}
+
+impl User {
+    /// whether the user may read `path`, which is inside one of the data
+    /// roots and inside their directory, or has its parent there when it
+    /// does not exist yet. a user who is not an admin reads nothing without
+    /// a directory
+    pub fn may_read(&self, path: &Path) -> bool {
+        let roots = &config().data_roots;
+        let own = match (&self.root, self.admin) {
+            (_, true) => None,
+            (Some(root), false) => Some(root),
+            (None, false) => return false,
+        };
+        if roots.is_empty() && own.is_none() {
+            return true;
+        }
+        let Some(path) = path
+            .canonicalize()
+            .ok()
+            .or_else(|| path.parent()?.canonicalize().ok())
+        else {
+            return false;
+        };
+        (roots.is_empty() || roots.iter().any(|x| path.starts_with(x)))
+            && own.is_none_or(|x| path.starts_with(x))
+    }
+
+    pub fn check_path(&self, path: &Path) -> Result<(), ServerFnError> {
+        if self.root.is_none() && !self.admin {
+            Err(ServerFnError::new(format!(
+                "{} has no directory of files, add them again with --root or set upload_dir",
+                self.name
+            )))
+        } else if self.may_read(path) {
+            Ok(())
+        } else {
+            Err(ServerFnError::new(format!(
+                "{} is outside the files of {}",
+                path.display(),
+                self.name
+            )))
+        }
+    }
+
+    /// whether the user sees what `owner` printed
+    pub fn sees(&self, owner: &str) -> bool {
+        self.admin || self.name == owner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the pbkdf2-hmac-sha256 vectors of rfc 7914, cut to the 32 bytes kept
+    #[test]
+    fn pbkdf2_rfc_7914() {
+        let hex = |x: [u8; 32]| x.iter().map(|x| format!("{x:02x}")).collect::<String>();
+        assert_eq!(
+            hex(pbkdf2(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+        assert_eq!(
+            hex(pbkdf2(b"Password", b"NaCl", 80000)),
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"
+        );
+    }
+
+    #[test]
+    fn verify_stored_hash() {
+        let salt = b"0123456789abcdef";
+        let stored = format!(
+            "pbkdf2-sha256$1000${}${}",
+            STANDARD_NO_PAD.encode(salt),
+            STANDARD_NO_PAD.encode(pbkdf2(b"secret", salt, 1000))
+        );
+        assert!(verify_password("secret", &stored));
+        assert!(!verify_password("Secret", &stored));
+        assert!(!verify_password("secret", "pbkdf2-sha256$1000$AAAA"));
+        assert!(!verify_password("secret", ""));
+    }
+}
@@ -0,0 +1,182 @@
+//! the settings of the server read once at startup from `kvg.toml`, or the
+//! file named by `KVG_CONFIG`, each of them overridden by its `KVG_*`
+//! environment variable
+
+use crate::app::{cards::PageLayout, i18n::Lang};
+use serde::Deserialize;
+use std::{
+    net::SocketAddr,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const DEFAULT_PATH: &str = "kvg.toml";
+/// how many mebibytes of parsed sheets are cached when not configured
+const DEFAULT_CACHE_MIB: usize = 256;
+
+/// the settings as written in the file, every one of them optional
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    listen: Option<String>,
+    data_roots: Option<Vec<PathBuf>>,
+    upload_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    cache_mib: Option<usize>,
+    layout: Option<PageLayout>,
+    language: Option<String>,
+}
+
+pub struct Config {
+    /// the address to listen on instead of the one of the leptos options
+    pub listen: Option<SocketAddr>,
+    /// the directories files are read from, any file when empty
+    pub data_roots: Vec<PathBuf>,
+    /// the parent of the directory of every user without their own
+    pub upload_dir: Option<PathBuf>,
+    /// where the accounts, print history and audit trail are kept
+    pub data_dir: PathBuf,
+    /// how many bytes of parsed sheets are kept at most
+    pub cache_size: usize,
+    /// the page layout of a new card set
+    pub layout: PageLayout,
+    /// the language of the ui before the user picks one
+    pub language: Lang,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen: None,
+            data_roots: Vec::new(),
+            upload_dir: None,
+            data_dir: PathBuf::from("data"),
+            cache_size: DEFAULT_CACHE_MIB * 1024 * 1024,
+            layout: PageLayout::default(),
+            language: Lang::default(),
+        }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// the loaded settings, the defaults before [`load`]
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// reads, overrides and checks the settings, telling what is wrong with them
+pub fn load() -> Result<&'static Config, String> {
+    let config = read()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|x| !x.trim().is_empty())
+}
+
+fn read() -> Result<Config, String> {
+    let (path, required) = match env("KVG_CONFIG") {
+        Some(x) => (PathBuf::from(x), true),
+        None => (PathBuf::from(DEFAULT_PATH), false),
+    };
+    let mut file = match std::fs::read_to_string(&path) {
+        Ok(text) => toml::from_str::<ConfigFile>(&text)
+            .map_err(|e| format!("{} is not valid:\n{e}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => ConfigFile::default(),
+        Err(e) => return Err(format!("can not read {}: {e}", path.display())),
+    };
+
+    if let Some(x) = env("KVG_LISTEN") {
+        file.listen = Some(x);
+    }
+    if let Some(x) = std::env::var_os("KVG_DATA_ROOTS").filter(|x| !x.is_empty()) {
+        file.data_roots = Some(std::env::split_paths(&x).collect());
+    }
+    if let Some(x) = env("KVG_UPLOAD_DIR") {
+        file.upload_dir = Some(x.into());
+    }
+    if let Some(x) = env("KVG_DATA_DIR") {
+        file.data_dir = Some(x.into());
+    }
+    if let Some(x) = env("KVG_CACHE_MIB") {
+        let mib = x
+            .trim()
+            .parse()
+            .map_err(|_| format!("KVG_CACHE_MIB={x} is not a whole number"))?;
+        file.cache_mib = Some(mib);
+    }
+    if let Some(x) = env("KVG_LANGUAGE") {
+        file.language = Some(x);
+    }
+    let mut layout = file.layout.unwrap_or_default();
+    if let Some(x) = env("KVG_PAGE_COLUMNS") {
+        layout.columns = x
+            .trim()
+            .parse::<NonZeroUsize>()
+            .map_err(|_| format!("KVG_PAGE_COLUMNS={x} is not a number above 0"))?;
+    }
+    if let Some(x) = env("KVG_PAGE_ROWS") {
+        layout.rows = Some(
+            x.trim()
+                .parse::<NonZeroUsize>()
+                .map_err(|_| format!("KVG_PAGE_ROWS={x} is not a number above 0"))?,
+        );
+    }
+    layout
+        .check()
+        .map_err(|e| format!("the page layout is not valid: {e}"))?;
+
+    let listen = file
+        .listen
+        .map(|x| {
+            x.trim()
+                .parse::<SocketAddr>()
+                .map_err(|_| format!("listen = {x:?} is not an address like 127.0.0.1:3000"))
+        })
+        .transpose()?;
+    let data_roots = file
+        .data_roots
+        .unwrap_or_default()
+        .iter()
+        .map(|x| existing_dir("data root", x))
+        .collect::<Result<_, _>>()?;
+    let upload_dir = file
+        .upload_dir
+        .map(|x| existing_dir("upload_dir", &x))
+        .transpose()?;
+    let cache_mib = file.cache_mib.unwrap_or(DEFAULT_CACHE_MIB);
+    if cache_mib == 0 {
+        return Err("cache_mib must be above 0".to_string());
+    }
+    let cache_size = cache_mib
+        .checked_mul(1024 * 1024)
+        .ok_or_else(|| format!("cache_mib = {cache_mib} is too large"))?;
+    let language = match file.language {
+        Some(x) => Lang::from_code(&x)
+            .ok_or_else(|| format!("language = {x:?} is not one of \"ar\" or \"en\""))?,
+        None => Lang::default(),
+    };
+    Ok(Config {
+        listen,
+        data_roots,
+        upload_dir,
+        data_dir: file.data_dir.unwrap_or_else(|| PathBuf::from("data")),
+        cache_size,
+        layout,
+        language,
+    })
+}
+
+/// `path` made absolute, failing when it is not a directory
+fn existing_dir(setting: &str, path: &Path) -> Result<PathBuf, String> {
+    let dir = path
+        .canonicalize()
+        .map_err(|e| format!("{setting} {} can not be used: {e}", path.display()))?;
+    if dir.is_dir() {
+        Ok(dir)
+    } else {
+        Err(format!("{setting} {} is not a directory", path.display()))
+    }
+}
@@ -2,33 +2,59 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
+    if let Err(e) = serve().await {
+        eprintln!("kvg: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn serve() -> Result<(), String> {
     use axum::Router;
     use leptos::logging::log;
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use kvg::app::*;
 
-    let conf = get_configuration(None).unwrap();
-    let addr = conf.leptos_options.site_addr;
-    let leptos_options = conf.leptos_options;
+    let config = kvg::config::load()?;
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|x| x == "adduser") {
+        return kvg::auth::add_user_command(&args[1..]);
+    }
+
+    let conf = get_configuration(None)
+        .map_err(|e| format!("the leptos options are not valid: {e}"))?;
+    let mut leptos_options = conf.leptos_options;
+    if let Some(listen) = config.listen {
+        leptos_options.site_addr = listen;
+    }
+    let addr = leptos_options.site_addr;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
 
     let app = Router::new()
+        .nest("/api/v1", kvg::api::router())
+        .nest("/events", kvg::watch::router())
         .leptos_routes(&leptos_options, routes, {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
         .fallback(leptos_axum::file_and_error_handler(shell))
+        .layer(axum::middleware::from_fn(kvg::auth::require_login))
         .with_state(leptos_options);
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
-    log!("listening on http://{}", &addr);
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
+    let listener = tokio::net::TcpListener::bind(&addr)
         .await
-        .unwrap();
+        .map_err(|e| format!("can not listen on {addr}: {e}"))?;
+    log!("listening on http://{}", &addr);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .map_err(|e| format!("the server stopped: {e}"))
 }
 
 #[cfg(not(feature = "ssr"))]
@@ -0,0 +1,98 @@
+//! server sent events telling the cards view that its source file changed on
+//! disk, nested under `/events` by the server
+
+use axum::{
+    Extension, Router,
+    extract::{ConnectInfo, Query},
+    http::StatusCode,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use futures::stream;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Deserialize;
+use std::{convert::Infallible, net::SocketAddr, path::PathBuf, time::Duration};
+use tokio::sync::mpsc;
+
+use crate::auth::User;
+
+/// how long to wait for the writes of a single save to settle
+const SETTLE: Duration = Duration::from_millis(500);
+
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new().route("/watch", get(watch))
+}
+
+#[derive(Deserialize)]
+struct WatchQuery {
+    path: PathBuf,
+}
+
+/// sends a `changed` event every time the file at `path` is saved
+async fn watch(
+    Extension(user): Extension<User>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<WatchQuery>,
+) -> Response {
+    let allowed = user.check_path(&query.path);
+    let recorded = crate::app::audit::record_by(
+        Some(&user),
+        Some(addr.ip()),
+        "watch",
+        &query.path,
+        None,
+        &allowed,
+        |_| None,
+    );
+    if let Err(e) = recorded {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    if allowed.is_err() {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let path = match query.path.canonicalize() {
+        Ok(x) => x,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let Some(dir) = path.parent().map(|x| x.to_path_buf()) else {
+        return (StatusCode::BAD_REQUEST, "the path has no parent").into_response();
+    };
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    let file = path.clone();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.contains(&file)
+        {
+            let _ = tx.send(());
+        }
+    });
+    // spreadsheet programs save by replacing the file, so its directory is
+    // watched instead of the file itself
+    let watcher = watcher.and_then(|mut watcher| {
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    let watcher = match watcher {
+        Ok(x) => x,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    // the watcher lives as long as the stream, it stops when the client leaves
+    let events = stream::unfold((rx, watcher), |(mut rx, watcher)| async move {
+        rx.recv().await?;
+        tokio::time::sleep(SETTLE).await;
+        while rx.try_recv().is_ok() {}
+        let event = Event::default().event("changed").data("");
+        Some((Ok::<_, Infallible>(event), (rx, watcher)))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}